@@ -0,0 +1,267 @@
+use crate::{hazard::Hazard, step_toward, Boundary, Portal};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fs, io, path::Path};
+
+// one of the built-in levels, completed by growing the snake to the
+// target length; the snake starts on the left of the middle row, which
+// the maps keep free
+pub struct Level {
+    pub name: &'static str,
+    // one string per row, '#' for an obstacle, a digit for a portal, each
+    // of them twice for the two ends, and '<', '>', '^', 'v' for a hazard
+    // bouncing left and right or up and down, 'X' for one bouncing
+    // diagonally
+    pub map: &'static [&'static str],
+    // hazards walking from corner to corner in straight lines and back
+    pub patrols: &'static [&'static [(usize, usize)]],
+    // milliseconds
+    pub step_length: u64,
+    pub boundary: Boundary,
+    // set by the level, not taken from the settings, so that the
+    // level plays the same for everyone
+    pub power_ups: bool,
+    pub target: usize,
+}
+
+impl Level {
+    pub fn width(&self) -> usize {
+        self.map[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn obstacles(&self) -> Vec<(usize, usize)> {
+        self.cells(b'#').collect()
+    }
+
+    pub fn portals(&self) -> Vec<Portal> {
+        (b'1'..=b'9')
+            .filter_map(|digit| {
+                let mut ends = self.cells(digit);
+                Some([ends.next()?, ends.next()?])
+            })
+            .collect()
+    }
+
+    pub fn hazards(&self) -> Vec<Hazard> {
+        let bouncing = [
+            (b'<', (-1, 0)),
+            (b'>', (1, 0)),
+            (b'^', (0, -1)),
+            (b'v', (0, 1)),
+            (b'X', (1, 1)),
+        ]
+        .into_iter()
+        .flat_map(|(tile, offsets)| {
+            self.cells(tile)
+                .map(move |position| Hazard::bounce(position, offsets))
+        });
+        let patrolling = self.patrols.iter().map(|corners| {
+            let mut path = vec![corners[0]];
+            let mut cell = corners[0];
+            for &corner in &corners[1..] {
+                while cell != corner {
+                    cell = (step_toward(cell.0, corner.0), step_toward(cell.1, corner.1));
+                    path.push(cell);
+                }
+            }
+            Hazard::patrol(path)
+        });
+
+        bouncing.chain(patrolling).collect()
+    }
+
+    // the cells of the map with the tile
+    fn cells(&self, tile: u8) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.map.iter().enumerate().flat_map(move |(y, row)| {
+            row.bytes()
+                .enumerate()
+                .filter(move |&(_, other)| other == tile)
+                .map(move |(x, _)| (x, y))
+        })
+    }
+
+    // like "26x11 board with walls, 200 ms per step, reach length 14"
+    pub fn description(&self) -> String {
+        format!(
+            "{}x{} board {}, {} ms per step, reach length {}",
+            self.width(),
+            self.height(),
+            match self.boundary {
+                Boundary::Wrap => "wrapping around",
+                Boundary::Walls => "with walls",
+            },
+            self.step_length,
+            self.target
+        )
+    }
+}
+
+// smaller boards, more obstacles, portals and hazards, faster snakes
+// and longer targets
+pub const LEVELS: [Level; 6] = [
+    Level {
+        name: "Open field",
+        map: &[
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+            "..............................",
+        ],
+        patrols: &[],
+        step_length: 250,
+        boundary: Boundary::Wrap,
+        power_ups: false,
+        target: 10,
+    },
+    Level {
+        name: "Pillars",
+        map: &[
+            "............................",
+            "............................",
+            "......##............##......",
+            "......##............##......",
+            "............................",
+            "............................",
+            "............................",
+            "............................",
+            "......##............##......",
+            "......##............##......",
+            "............................",
+            "............................",
+        ],
+        patrols: &[],
+        step_length: 220,
+        boundary: Boundary::Wrap,
+        power_ups: false,
+        target: 12,
+    },
+    Level {
+        name: "Bars",
+        map: &[
+            "..........................",
+            "..........................",
+            ".....################.....",
+            "..........................",
+            "..........................",
+            "..........................",
+            "..........................",
+            "..........................",
+            ".....################.....",
+            "..........................",
+            "..........................",
+        ],
+        patrols: &[],
+        step_length: 200,
+        boundary: Boundary::Walls,
+        power_ups: false,
+        target: 14,
+    },
+    Level {
+        name: "Corridors",
+        map: &[
+            "........................",
+            "..............#.........",
+            "..............#..v.#....",
+            "..............#....#....",
+            "..............#....#....",
+            "..............#....#....",
+            "...................#....",
+            "........#..........#....",
+            "........#..........#....",
+            "........#...............",
+            "........................",
+        ],
+        patrols: &[],
+        step_length: 170,
+        boundary: Boundary::Walls,
+        power_ups: false,
+        target: 16,
+    },
+    Level {
+        name: "Rooms",
+        map: &[
+            "..........#...........",
+            "..1.......#.....X.....",
+            "..........#...........",
+            "..........#.......1...",
+            "..............#######.",
+            "......................",
+            "..#######.............",
+            "...........#..........",
+            "...........#..........",
+            "...........#..........",
+        ],
+        patrols: &[],
+        step_length: 150,
+        boundary: Boundary::Wrap,
+        power_ups: false,
+        target: 18,
+    },
+    Level {
+        name: "Maze",
+        map: &[
+            ".........1..........",
+            ".######....######...",
+            "....................",
+            "...#....#####....#..",
+            "....................",
+            "...#....#####....#..",
+            "....................",
+            ".######....######...",
+            ".........1..........",
+        ],
+        // along the rows above and below the start and down the right
+        patrols: &[&[(1, 2), (18, 2), (18, 6), (1, 6)]],
+        step_length: 120,
+        boundary: Boundary::Walls,
+        power_ups: false,
+        target: 20,
+    },
+];
+
+// the levels completed so far, kept in a JSON file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Progress {
+    // indices into `LEVELS`
+    pub completed: BTreeSet<usize>,
+}
+
+impl Progress {
+    // nothing completed when there is no file yet
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn is_completed(&self, level: usize) -> bool {
+        self.completed.contains(&level)
+    }
+
+    // the first level and the ones after completed levels can be played
+    pub fn is_unlocked(&self, level: usize) -> bool {
+        level == 0 || self.is_completed(level - 1)
+    }
+
+    // returns whether the level was not completed before
+    pub fn complete(&mut self, level: usize) -> bool {
+        self.completed.insert(level)
+    }
+}
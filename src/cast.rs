@@ -0,0 +1,115 @@
+use crate::{replay::Replay, GameState};
+use serde_json::json;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+// passes everything drawn on to `out` and also writes it to an asciinema
+// v2 recording, one event for every flush, which is one frame of `draw`
+pub struct CastRecorder<W: Write> {
+    out: W,
+    cast: BufWriter<File>,
+    frame: Vec<u8>,
+    // frames are timed by the clock while recording a live game,
+    // otherwise the time of every frame is set before drawing it
+    start: Option<Instant>,
+    time: Duration,
+}
+
+impl<W: Write> CastRecorder<W> {
+    pub fn create(out: W, path: &Path, (columns, rows): (usize, usize)) -> io::Result<Self> {
+        let mut cast = BufWriter::new(File::create(path)?);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let header = json!({
+            "version": 2,
+            "width": columns,
+            "height": rows,
+            "timestamp": timestamp,
+            "title": "snake",
+        });
+        writeln!(cast, "{header}")?;
+
+        Ok(Self {
+            out,
+            cast,
+            frame: Vec::new(),
+            start: Some(Instant::now()),
+            time: Duration::ZERO,
+        })
+    }
+
+    // stops using the clock, the following frames happen at this time
+    pub fn set_time(&mut self, time: Duration) {
+        self.start = None;
+        self.time = time;
+    }
+}
+
+impl<W: Write> Write for CastRecorder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.out.write(buf)?;
+        self.frame.extend_from_slice(&buf[..written]);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()?;
+        if self.frame.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(start) = self.start {
+            self.time = start.elapsed();
+        }
+        let event = json!([
+            self.time.as_secs_f64(),
+            "o",
+            String::from_utf8_lossy(&self.frame)
+        ]);
+        writeln!(self.cast, "{event}")?;
+        self.cast.flush()?;
+        self.frame.clear();
+
+        Ok(())
+    }
+}
+
+// terminal size the game needs for a board and the text below it
+pub fn terminal_size(width: usize, height: usize, snakes: usize) -> (usize, usize) {
+    ((width + 2).max(80), height + 7 + snakes)
+}
+
+// draws every step of the replay into an asciinema recording, timed
+// like the game was played, including the longer steps of slow-motion
+pub fn export(replay: &Replay, path: &Path) -> io::Result<()> {
+    let size = terminal_size(replay.width, replay.height, replay.snakes.len());
+    let mut game = replay.game(CastRecorder::create(io::sink(), path, size)?);
+    let mut time = Duration::ZERO;
+    loop {
+        game.out.set_time(time);
+        let state = match game.state {
+            GameState::Running => "",
+            GameState::Won => "\n\rThe board is full!",
+            GameState::Lost => "\n\rGame over!",
+        };
+        let snakes = game.scoreboard();
+        game.add_snake_to_board();
+        game.draw(&format!("tick {}{snakes}{state}", game.tick))?;
+        game.remove_snake_from_board();
+
+        if game.state != GameState::Running || game.tick >= replay.len() as u64 {
+            break;
+        }
+        time += game.current_step_length();
+        game.step();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,392 @@
+use crate::{
+    check_board_size, controller::KeyboardController, menu::Mode, Boundary, Direction,
+    BOARD_HEIGHT, BOARD_WIDTH, STEP_LENGTH,
+};
+use crossterm::event::KeyCode;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+    str::FromStr,
+};
+
+// settings read from a JSON file, everything missing keeps its default:
+//
+//     {"layout": "vim", "keys": {"quit": ["esc"], "snapshot": ["p", "f2"]}}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // keyboard layout the key bindings start from
+    pub layout: Layout,
+    // keys replacing the ones of the layout for these actions
+    pub keys: BTreeMap<Action, Vec<Key>>,
+    pub settings: Settings,
+}
+
+// how new games are set up, changed in the menu
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub width: usize,
+    pub height: usize,
+    // milliseconds
+    pub step_length: u64,
+    // colours of the board, the terminal's own ones if None
+    pub theme: Option<String>,
+    pub boundary: Boundary,
+    pub mode: Mode,
+    // whether some of the food is power-ups
+    pub power_ups: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            width: BOARD_WIDTH,
+            height: BOARD_HEIGHT,
+            step_length: STEP_LENGTH,
+            theme: None,
+            boundary: Boundary::default(),
+            mode: Mode::default(),
+            power_ups: false,
+        }
+    }
+}
+
+impl Config {
+    // the default config when there is no file yet
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let config: Self = serde_json::from_str(&text)?;
+                check_board_size(config.settings.width, config.settings.height)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+                Ok(config)
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn key_bindings(&self) -> KeyBindings {
+        let mut bindings = self.layout.key_bindings();
+        for (&action, keys) in &self.keys {
+            bindings.keys.insert(action, keys.clone());
+        }
+
+        bindings
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    #[default]
+    Qwerty,
+    Azerty,
+    Dvorak,
+    Vim,
+    Numpad,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 5] = [
+        Layout::Qwerty,
+        Layout::Azerty,
+        Layout::Dvorak,
+        Layout::Vim,
+        Layout::Numpad,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Layout::Qwerty => "qwerty",
+            Layout::Azerty => "azerty",
+            Layout::Dvorak => "dvorak",
+            Layout::Vim => "vim",
+            Layout::Numpad => "numpad",
+        }
+    }
+
+    // the first player steers with the keys where W,A,S,D are on a qwerty
+    // keyboard (or h,j,k,l and the numpad) and the second one with the arrow
+    // keys; q quits unless the layout steers with it
+    fn key_bindings(self) -> KeyBindings {
+        let (up, left, down, right) = match self {
+            Layout::Qwerty => ('w', 'a', 's', 'd'),
+            Layout::Azerty => ('z', 'q', 's', 'd'),
+            Layout::Dvorak => (',', 'a', 'o', 'e'),
+            Layout::Vim => ('k', 'h', 'j', 'l'),
+            Layout::Numpad => ('8', '4', '2', '6'),
+        };
+        let quit = if self == Layout::Azerty {
+            vec![Key(KeyCode::Esc)]
+        } else {
+            vec![Key(KeyCode::Char('q')), Key(KeyCode::Esc)]
+        };
+        let char = |c| vec![Key(KeyCode::Char(c))];
+
+        KeyBindings {
+            keys: BTreeMap::from([
+                (Action::Up, char(up)),
+                (Action::Left, char(left)),
+                (Action::Down, char(down)),
+                (Action::Right, char(right)),
+                (Action::SecondUp, vec![Key(KeyCode::Up)]),
+                (Action::SecondLeft, vec![Key(KeyCode::Left)]),
+                (Action::SecondDown, vec![Key(KeyCode::Down)]),
+                (Action::SecondRight, vec![Key(KeyCode::Right)]),
+                (Action::Quit, quit),
+                (Action::Save, char('x')),
+                (Action::Snapshot, char('v')),
+                (
+                    Action::Controls,
+                    vec![Key(KeyCode::Char('?')), Key(KeyCode::F(1))],
+                ),
+            ]),
+        }
+    }
+}
+
+// everything a key can be bound to during a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Up,
+    Left,
+    Down,
+    Right,
+    SecondUp,
+    SecondLeft,
+    SecondDown,
+    SecondRight,
+    Quit,
+    Save,
+    Snapshot,
+    Controls,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::Up,
+        Action::Left,
+        Action::Down,
+        Action::Right,
+        Action::SecondUp,
+        Action::SecondLeft,
+        Action::SecondDown,
+        Action::SecondRight,
+        Action::Quit,
+        Action::Save,
+        Action::Snapshot,
+        Action::Controls,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Left => "left",
+            Action::Down => "down",
+            Action::Right => "right",
+            Action::SecondUp => "up, second player",
+            Action::SecondLeft => "left, second player",
+            Action::SecondDown => "down, second player",
+            Action::SecondRight => "right, second player",
+            Action::Quit => "quit",
+            Action::Save => "save and quit",
+            Action::Snapshot => "save the board as SVG",
+            Action::Controls => "show the controls",
+        }
+    }
+
+    // the steering actions of the first and the second player
+    fn steering(player: usize) -> [(Action, Direction); 4] {
+        if player == 0 {
+            [
+                (Action::Up, Direction::Up),
+                (Action::Left, Direction::Left),
+                (Action::Down, Direction::Down),
+                (Action::Right, Direction::Right),
+            ]
+        } else {
+            [
+                (Action::SecondUp, Direction::Up),
+                (Action::SecondLeft, Direction::Left),
+                (Action::SecondDown, Direction::Down),
+                (Action::SecondRight, Direction::Right),
+            ]
+        }
+    }
+}
+
+// the keys bound to each action
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    keys: BTreeMap<Action, Vec<Key>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Layout::default().key_bindings()
+    }
+}
+
+impl KeyBindings {
+    // the first action the key is bound to
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| self.keys(*action).contains(&Key(key)))
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    // all keys of the action, for showing them
+    pub fn describe(&self, action: Action) -> String {
+        let keys: Vec<_> = self.keys(action).iter().map(Key::label).collect();
+        if keys.is_empty() {
+            "-".to_owned()
+        } else {
+            keys.join(", ")
+        }
+    }
+
+    // the keys steering a single player, like "W,A,S,D or ↑,←,↓,→"
+    pub fn steering(&self) -> String {
+        (0..2)
+            .map(|player| {
+                Action::steering(player)
+                    .iter()
+                    .map(|&(action, _)| {
+                        self.keys(action).first().map_or("-".to_owned(), Key::label)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
+
+    // a single player can use the keys of both players
+    pub fn keyboard(&self, player: usize, players: usize) -> KeyboardController {
+        let players = if players == 1 {
+            vec![0, 1]
+        } else {
+            vec![player]
+        };
+        KeyboardController::new(
+            players
+                .into_iter()
+                .flat_map(Action::steering)
+                .flat_map(|(action, direction)| {
+                    self.keys(action).iter().map(move |key| (key.0, direction))
+                })
+                .collect(),
+        )
+    }
+}
+
+// a key in the config, named like "w", "up", "esc", "space" or "f1"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Key(pub KeyCode);
+
+impl Key {
+    // how the key is shown on the screen
+    pub fn label(&self) -> String {
+        match self.0 {
+            KeyCode::Up => "↑".to_owned(),
+            KeyCode::Down => "↓".to_owned(),
+            KeyCode::Left => "←".to_owned(),
+            KeyCode::Right => "→".to_owned(),
+            KeyCode::Char(' ') => "Space".to_owned(),
+            KeyCode::Char(c) => c.to_uppercase().to_string(),
+            _ => {
+                let mut name = self.to_string();
+                name[..1].make_ascii_uppercase();
+                name
+            }
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key(KeyCode::Char(c)));
+        }
+
+        Ok(Key(match s.to_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(format!("unknown key '{s}'")),
+            },
+        }))
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Insert => write!(f, "insert"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            // keys that cannot be configured are never created
+            key => write!(f, "{key:?}"),
+        }
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,317 @@
+use crate::{external::ProcessController, Board, Boundary, Direction, Portal, Snake};
+#[cfg(feature = "terminal")]
+use crossterm::event::KeyCode;
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display, Write},
+    io,
+    str::FromStr,
+    time::Duration,
+};
+
+// decides where a snake goes next; any snake on the board
+// can be driven by any controller
+pub trait Controller {
+    // called once per game step, before the snakes move;
+    // directions reversing the snake are ignored by the game
+    fn next_direction(&mut self, view: &GameView) -> Direction;
+
+    // offered every key pressed during the game,
+    // returns whether the key was used by this controller
+    #[cfg(feature = "terminal")]
+    fn handle_key(&mut self, _key: KeyCode) -> bool {
+        false
+    }
+
+    // number of steps the controller failed to decide in time
+    fn timeouts(&self) -> u32 {
+        0
+    }
+
+    // a controller that stopped following the rules, e.g. an external
+    // bot sending garbage, gets its snake removed from the game
+    fn disqualification(&self) -> Option<&str> {
+        None
+    }
+}
+
+// read-only view of the game from the perspective of one snake
+pub struct GameView<'a> {
+    pub tick: u64,
+    pub board: &'a Board,
+    pub snakes: &'a [Snake],
+    // index of the snake the controller is driving
+    pub snake: usize,
+    pub boundary: Boundary,
+    pub portals: &'a [Portal],
+}
+
+impl<'a> GameView<'a> {
+    pub fn snake(&self) -> &'a Snake {
+        &self.snakes[self.snake]
+    }
+
+    pub fn width(&self) -> usize {
+        self.board[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.board.len()
+    }
+
+    // the tile one step away, or the one after the other end of a portal;
+    // None if there is a wall in the way
+    pub fn step(&self, position: (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        crate::next_position(
+            self.boundary,
+            self.portals,
+            direction,
+            position,
+            self.width(),
+            self.height(),
+        )
+    }
+
+    // whether an obstacle, a portal or any living snake occupies the tile
+    pub fn is_occupied(&self, (x, y): (usize, usize)) -> bool {
+        self.board[y][x].is_fixed()
+            || self
+                .snakes
+                .iter()
+                .filter(|snake| snake.alive)
+                .flat_map(|snake| snake.whole_snake())
+                .any(|tile| usize::from(tile.x) == x && usize::from(tile.y) == y)
+    }
+}
+
+// turns key presses into directions, one queued key per step
+#[cfg(feature = "terminal")]
+pub struct KeyboardController {
+    bindings: Vec<(KeyCode, Direction)>,
+    queue: VecDeque<Direction>,
+}
+
+#[cfg(feature = "terminal")]
+impl KeyboardController {
+    pub fn new(bindings: Vec<(KeyCode, Direction)>) -> Self {
+        Self {
+            bindings,
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn wasd() -> Self {
+        Self::new(vec![
+            (KeyCode::Char('w'), Direction::Up),
+            (KeyCode::Char('a'), Direction::Left),
+            (KeyCode::Char('s'), Direction::Down),
+            (KeyCode::Char('d'), Direction::Right),
+        ])
+    }
+
+    pub fn arrows() -> Self {
+        Self::new(vec![
+            (KeyCode::Up, Direction::Up),
+            (KeyCode::Left, Direction::Left),
+            (KeyCode::Down, Direction::Down),
+            (KeyCode::Right, Direction::Right),
+        ])
+    }
+
+    // the direction bound to the key, if any
+    pub fn direction(&self, key: KeyCode) -> Option<Direction> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|&(_, direction)| direction)
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl Default for KeyboardController {
+    fn default() -> Self {
+        let mut controller = Self::wasd();
+        controller.bindings.extend(Self::arrows().bindings);
+
+        controller
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl Controller for KeyboardController {
+    fn next_direction(&mut self, view: &GameView) -> Direction {
+        self.queue
+            .pop_front()
+            .unwrap_or_else(|| view.snake().direction())
+    }
+
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        match self.direction(key) {
+            Some(direction) => {
+                self.queue.push_back(direction);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// heads for the nearest reachable food, or just tries to stay alive
+// if there is none
+pub struct BotController;
+
+impl Controller for BotController {
+    fn next_direction(&mut self, view: &GameView) -> Direction {
+        let (width, height) = (view.width(), view.height());
+        let head = view.snake().head();
+        let start = (usize::from(head.x), usize::from(head.y));
+        let current = view.snake().direction();
+        let directions = [
+            current,
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ];
+
+        // breadth-first search remembering the first step of every path
+        let mut first_step = vec![vec![None; width]; height];
+        let mut queue = VecDeque::new();
+        for direction in directions {
+            let Some(next) = view.step(start, direction) else {
+                continue;
+            };
+            if first_step[next.1][next.0].is_none() && !view.is_occupied(next) {
+                first_step[next.1][next.0] = Some(direction);
+                queue.push_back(next);
+            }
+        }
+        let fallback = queue.front().map(|&(x, y)| first_step[y][x].unwrap());
+
+        while let Some((x, y)) = queue.pop_front() {
+            let direction = first_step[y][x].unwrap();
+            if view.board[y][x].has_food() {
+                return direction;
+            }
+            for step in directions {
+                let Some(next) = view.step((x, y), step) else {
+                    continue;
+                };
+                if next != start && first_step[next.1][next.0].is_none() && !view.is_occupied(next)
+                {
+                    first_step[next.1][next.0] = Some(direction);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        fallback.unwrap_or(current)
+    }
+}
+
+// replays a fixed sequence of directions, one per step,
+// then keeps going straight
+pub struct ScriptedController {
+    script: VecDeque<Option<Direction>>,
+}
+
+impl ScriptedController {
+    pub fn new(script: impl IntoIterator<Item = Option<Direction>>) -> Self {
+        Self {
+            script: script.into_iter().collect(),
+        }
+    }
+}
+
+impl Controller for ScriptedController {
+    fn next_direction(&mut self, view: &GameView) -> Direction {
+        self.script
+            .pop_front()
+            .flatten()
+            .unwrap_or_else(|| view.snake().direction())
+    }
+}
+
+// controller as chosen on the command line
+#[derive(Debug, Clone)]
+pub enum ControllerKind {
+    Keyboard,
+    Bot,
+    Script(Vec<Option<Direction>>),
+    External(String),
+}
+
+impl FromStr for ControllerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "keyboard" => Ok(ControllerKind::Keyboard),
+            "bot" => Ok(ControllerKind::Bot),
+            _ => match s.strip_prefix("script:") {
+                Some(moves) => moves
+                    .chars()
+                    .map(|c| match c {
+                        'w' => Ok(Some(Direction::Up)),
+                        'a' => Ok(Some(Direction::Left)),
+                        's' => Ok(Some(Direction::Down)),
+                        'd' => Ok(Some(Direction::Right)),
+                        '.' => Ok(None),
+                        _ => Err(format!("invalid move '{c}' in script")),
+                    })
+                    .collect::<std::result::Result<_, _>>()
+                    .map(ControllerKind::Script),
+                None => match s.strip_prefix("external:") {
+                    Some(command) => Ok(ControllerKind::External(command.to_owned())),
+                    None => Err(format!("unknown controller '{s}'")),
+                },
+            },
+        }
+    }
+}
+
+impl ControllerKind {
+    // keyboard controllers are created with the default key bindings
+    pub fn create(&self, move_time_limit: Duration) -> io::Result<Box<dyn Controller>> {
+        Ok(match self {
+            #[cfg(feature = "terminal")]
+            ControllerKind::Keyboard => Box::<KeyboardController>::default(),
+            #[cfg(not(feature = "terminal"))]
+            ControllerKind::Keyboard => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "keyboard controllers need the terminal",
+                ))
+            }
+            ControllerKind::Bot => Box::new(BotController),
+            ControllerKind::Script(moves) => {
+                Box::new(ScriptedController::new(moves.iter().copied()))
+            }
+            ControllerKind::External(command) => {
+                Box::new(ProcessController::spawn(command, move_time_limit)?)
+            }
+        })
+    }
+}
+
+impl Display for ControllerKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControllerKind::Keyboard => write!(f, "keyboard"),
+            ControllerKind::Bot => write!(f, "bot"),
+            ControllerKind::Script(moves) => {
+                write!(f, "script:")?;
+                moves.iter().try_for_each(|direction| {
+                    f.write_char(match direction {
+                        Some(Direction::Up) => 'w',
+                        Some(Direction::Left) => 'a',
+                        Some(Direction::Down) => 's',
+                        Some(Direction::Right) => 'd',
+                        None => '.',
+                    })
+                })
+            }
+            ControllerKind::External(command) => write!(f, "external:{command}"),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{Boundary, GameRng, Rules};
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+// the game of the day, the same for everyone playing on that date
+#[derive(Debug, Clone, PartialEq)]
+pub struct Challenge {
+    // like 2024-05-31
+    pub date: String,
+    pub seed: u64,
+    pub width: usize,
+    pub height: usize,
+    // milliseconds
+    pub step_length: u64,
+    pub boundary: Boundary,
+    pub rules: Rules,
+}
+
+impl Challenge {
+    // everything is picked by a random number generator seeded with a
+    // hash of the date; zen is left out, it would never be over
+    pub fn for_date(date: &str) -> Self {
+        let mut rng = GameRng::seed_from_u64(fnv(date.as_bytes()));
+        // sampled as u32 like the food, to get the same on every platform
+        let mut pick = |count: usize| rng.gen_range(0..count as u32) as usize;
+        let width = [20, 30, 40, 50][pick(4)];
+        let height = [10, 15, 20][pick(3)];
+        let step_length = [150, 200, 250, 300][pick(4)];
+        let boundary = Boundary::ALL[pick(Boundary::ALL.len())];
+        let rules = [Rules::Classic, Rules::TimeAttack, Rules::Survival][pick(3)];
+
+        Self {
+            date: date.to_owned(),
+            seed: rng.gen(),
+            width,
+            height,
+            step_length,
+            boundary,
+            rules,
+        }
+    }
+
+    // like "30x15 board with walls, survival, 200 ms per step"
+    pub fn description(&self) -> String {
+        format!(
+            "{}x{} board {}, {}, {} ms per step",
+            self.width,
+            self.height,
+            match self.boundary {
+                Boundary::Wrap => "wrapping around",
+                Boundary::Walls => "with walls",
+            },
+            self.rules.name(),
+            self.step_length
+        )
+    }
+}
+
+// how the daily challenges went, kept in a JSON file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyResults {
+    pub days: BTreeMap<String, DailyResult>,
+}
+
+// the one attempt of a day; it is written when the game starts,
+// so that leaving it does not give another try
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyResult {
+    pub score: u32,
+    pub length: usize,
+    pub finished: bool,
+}
+
+impl DailyResults {
+    // no results when there is no file yet
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn get(&self, date: &str) -> Option<&DailyResult> {
+        self.days.get(date)
+    }
+
+    pub fn is_finished(&self, date: &str) -> bool {
+        self.get(date).is_some_and(|result| result.finished)
+    }
+
+    // a finished day is never written again, it was the one attempt;
+    // returns whether the result was recorded
+    pub fn record(&mut self, date: &str, result: DailyResult) -> bool {
+        if self.is_finished(date) {
+            return false;
+        }
+        self.days.insert(date.to_owned(), result);
+
+        true
+    }
+
+    // the date and result of the best day so far, the earlier one on ties
+    pub fn best(&self) -> Option<(&str, &DailyResult)> {
+        self.days
+            .iter()
+            .rev()
+            .max_by_key(|(_, result)| result.score)
+            .map(|(date, result)| (date.as_str(), result))
+    }
+}
+
+// FNV-1a, the same on every platform unlike the standard library's hasher
+fn fnv(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finished_days_are_not_recorded_again() {
+        let mut results = DailyResults::default();
+        let started = DailyResult::default();
+        let finished = DailyResult {
+            score: 4,
+            length: 7,
+            finished: true,
+        };
+
+        assert!(results.record("2024-05-31", started.clone()));
+        assert!(results.record("2024-05-31", finished.clone()));
+        assert!(!results.record("2024-05-31", started));
+        assert_eq!(results.get("2024-05-31"), Some(&finished));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    controller::ScriptedController, Direction, GameRng, GameState, Snake, SnakeGame, BOARD_HEIGHT,
+    BOARD_WIDTH,
+};
+use std::io::{self, Sink};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Rewards {
+    // for every food eaten
+    pub food: f32,
+    // for running into a snake
+    pub death: f32,
+    // added every step, usually negative to make the agent hurry up
+    pub step: f32,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Self {
+            food: 1.0,
+            death: -1.0,
+            step: -0.01,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObservationKind {
+    // [3, height, width] tensor with one channel each for the
+    // head, the rest of the body and food
+    Grid,
+    // 11 values: danger straight ahead, to the right and to the left,
+    // the current direction one-hot encoded as up, right, down, left
+    // and whether the nearest food is up, right, down or left of the head
+    Features,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    pub width: usize,
+    pub height: usize,
+    pub rewards: Rewards,
+    pub observation: ObservationKind,
+    // episodes are cut off after this many steps
+    pub max_steps: Option<u64>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            width: BOARD_WIDTH,
+            height: BOARD_HEIGHT,
+            rewards: Rewards::default(),
+            observation: ObservationKind::Grid,
+            max_steps: None,
+        }
+    }
+}
+
+// flat row-major data together with its shape
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Info {
+    pub score: u32,
+    pub length: usize,
+    pub tick: u64,
+    // the snake filled the whole board
+    pub won: bool,
+    // the episode was cut off by `max_steps`
+    pub truncated: bool,
+}
+
+// Gym-style environment for training agents: a single snake on the board,
+// moved by calling `step` with an action, using the same rules as the game
+pub struct SnakeEnv {
+    config: EnvConfig,
+    game: SnakeGame<GameRng, Sink>,
+}
+
+impl SnakeEnv {
+    pub fn new(config: EnvConfig) -> Self {
+        let game = new_game(&config, 0);
+
+        Self { config, game }
+    }
+
+    // starts a new episode, the same seed always gives the same food
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game = new_game(&self.config, seed);
+
+        self.observe()
+    }
+
+    // turns the snake (reversing is ignored like in the game) and moves it;
+    // returns the observation, the reward, whether the episode is over
+    // and extra information
+    pub fn step(&mut self, action: Direction) -> (Observation, f32, bool, Info) {
+        let rewards = self.config.rewards;
+        let mut reward = 0.0;
+        if self.game.state == GameState::Running {
+            let score = self.game.snakes[0].score;
+            self.game.snakes[0].turn(action);
+            self.game.step();
+            let snake = &self.game.snakes[0];
+            reward += rewards.step + (snake.score - score) as f32 * rewards.food;
+            if !snake.alive {
+                reward += rewards.death;
+            }
+        }
+
+        let truncated = self
+            .config
+            .max_steps
+            .is_some_and(|max_steps| self.game.tick >= max_steps);
+        let done = self.game.state != GameState::Running || truncated;
+        let snake = &self.game.snakes[0];
+        let info = Info {
+            score: snake.score,
+            length: snake.len(),
+            tick: self.game.tick,
+            won: self.game.state == GameState::Won,
+            truncated,
+        };
+
+        (self.observe(), reward, done, info)
+    }
+
+    pub fn game(&self) -> &SnakeGame<GameRng, Sink> {
+        &self.game
+    }
+
+    pub fn observe(&self) -> Observation {
+        match self.config.observation {
+            ObservationKind::Grid => self.grid(),
+            ObservationKind::Features => self.features(),
+        }
+    }
+
+    fn grid(&self) -> Observation {
+        let (width, height) = (self.config.width, self.config.height);
+        let mut data = vec![0.0; 3 * width * height];
+        let index = |channel: usize, (x, y): (usize, usize)| (channel * height + y) * width + x;
+
+        let snake = &self.game.snakes[0];
+        if snake.alive {
+            for (i, tile) in snake.whole_snake().iter().enumerate() {
+                let channel = if i == 0 { 0 } else { 1 };
+                data[index(channel, tile.position())] = 1.0;
+            }
+        }
+        for (y, row) in self.game.board.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if tile.has_food() {
+                    data[index(2, (x, y))] = 1.0;
+                }
+            }
+        }
+
+        Observation {
+            shape: vec![3, height, width],
+            data,
+        }
+    }
+
+    fn features(&self) -> Observation {
+        let (width, height) = (self.config.width, self.config.height);
+        let snake = &self.game.snakes[0];
+        let head = snake.head().position();
+        let direction = snake.direction();
+        let clockwise = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ];
+        let turn = |quarters: usize| {
+            let current = clockwise.iter().position(|&d| d == direction).unwrap();
+            clockwise[(current + quarters) % 4]
+        };
+        let flag = |condition: bool| if condition { 1.0 } else { 0.0 };
+        let danger = |direction: Direction| {
+            flag(!self.game.is_safe(0, direction.apply(head, width, height)))
+        };
+
+        let mut data = vec![danger(turn(0)), danger(turn(1)), danger(turn(3))];
+        data.extend(clockwise.iter().map(|&d| flag(d == direction)));
+
+        // offsets to the nearest food, going around the board edges if shorter
+        let offset = |from: usize, to: usize, size: usize| {
+            let forward = (to + size - from) % size;
+            if forward * 2 <= size {
+                forward as isize
+            } else {
+                forward as isize - size as isize
+            }
+        };
+        let food = self
+            .game
+            .board
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, tile)| (x, y, tile)))
+            .filter(|(_, _, tile)| tile.has_food())
+            .map(|(x, y, _)| (offset(head.0, x, width), offset(head.1, y, height)))
+            .min_by_key(|(dx, dy)| dx.abs() + dy.abs());
+        let (dx, dy) = food.unwrap_or((0, 0));
+        data.extend([flag(dy < 0), flag(dx > 0), flag(dy > 0), flag(dx < 0)]);
+
+        Observation {
+            shape: vec![data.len()],
+            data,
+        }
+    }
+}
+
+fn new_game(config: &EnvConfig, seed: u64) -> SnakeGame<GameRng, Sink> {
+    let (width, height) = (config.width, config.height);
+    let mut game = SnakeGame::with_seed(width, height, io::sink(), seed);
+    // the snake is steered directly in `step`, this just keeps it going
+    game.add_snake(
+        Snake::spawn(0, 1, width, height),
+        Box::new(ScriptedController::new([])),
+    );
+    game.spawn_food();
+
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MIN_BOARD_SIZE;
+
+    // what the Python and JavaScript bindings run on their smallest boards
+    #[test]
+    fn episodes_on_the_smallest_board_play_to_the_end() {
+        let directions = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ];
+        for observation in [ObservationKind::Grid, ObservationKind::Features] {
+            let mut env = SnakeEnv::new(EnvConfig {
+                width: MIN_BOARD_SIZE,
+                height: MIN_BOARD_SIZE,
+                observation,
+                max_steps: Some(200),
+                ..Default::default()
+            });
+            for seed in 0..20 {
+                env.reset(seed);
+                let mut done = false;
+                let mut steps = 0;
+                while !done {
+                    let action = directions[(seed as usize + steps / 3) % 4];
+                    (_, _, done, _) = env.step(action);
+                    steps += 1;
+                }
+                assert!(steps <= 200);
+            }
+        }
+    }
+}
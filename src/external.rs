@@ -0,0 +1,217 @@
+use crate::{
+    controller::{Controller, GameView},
+    Direction, Portal, Tile,
+};
+use serde::Serialize;
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+const STARTUP_TIME: Duration = Duration::from_secs(1);
+
+// the game state sent to the bot as one line of JSON every step
+#[derive(Serialize)]
+struct BotState {
+    tick: u64,
+    width: usize,
+    height: usize,
+    // index of the bot's own snake in `snakes`
+    you: usize,
+    food: Vec<(usize, usize)>,
+    obstacles: Vec<(usize, usize)>,
+    portals: Vec<Portal>,
+    hazards: Vec<(usize, usize)>,
+    snakes: Vec<SnakeState>,
+}
+
+#[derive(Serialize)]
+struct SnakeState {
+    alive: bool,
+    score: u32,
+    direction: Direction,
+    // head first
+    body: Vec<(usize, usize)>,
+}
+
+impl BotState {
+    fn new(view: &GameView) -> Self {
+        let tiles = || {
+            view.board
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, tile)| (x, y, tile)))
+        };
+        let food = tiles()
+            .filter(|(_, _, tile)| tile.has_food())
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let tiles_of = |kind: Tile| {
+            tiles()
+                .filter(|(_, _, tile)| **tile == kind)
+                .map(|(x, y, _)| (x, y))
+                .collect()
+        };
+        let snakes = view
+            .snakes
+            .iter()
+            .map(|snake| SnakeState {
+                alive: snake.alive,
+                score: snake.score,
+                direction: snake.direction(),
+                body: snake
+                    .whole_snake()
+                    .iter()
+                    .map(|tile| (usize::from(tile.x), usize::from(tile.y)))
+                    .collect(),
+            })
+            .collect();
+
+        BotState {
+            tick: view.tick,
+            width: view.width(),
+            height: view.height(),
+            you: view.snake,
+            food,
+            obstacles: tiles_of(Tile::Obstacle),
+            portals: view.portals.to_vec(),
+            hazards: tiles_of(Tile::Hazard),
+            snakes,
+        }
+    }
+}
+
+// drives a snake by a child process: every step the game state is written
+// to its stdin and a direction (up, down, left or right) is read back from
+// its stdout; a bot that does not answer in time keeps going straight,
+// a bot that answers with anything else or exits is disqualified
+pub struct ProcessController {
+    child: Child,
+    stdin: ChildStdin,
+    replies: Receiver<String>,
+    // states sent whose replies were not read yet
+    pending: usize,
+    time_limit: Duration,
+    started: bool,
+    timeouts: u32,
+    disqualification: Option<String>,
+}
+
+impl ProcessController {
+    // the command is split on whitespace, no shell is involved
+    pub fn spawn(command: &str, time_limit: Duration) -> io::Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // reading blocks, so it is done on a separate thread
+        // which allows waiting for a reply with a timeout
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            replies,
+            pending: 0,
+            time_limit,
+            started: false,
+            timeouts: 0,
+            disqualification: None,
+        })
+    }
+
+    fn send_state(&mut self, view: &GameView) -> io::Result<()> {
+        let state = serde_json::to_string(&BotState::new(view))?;
+        writeln!(self.stdin, "{state}")?;
+        self.stdin.flush()?;
+        self.pending += 1;
+
+        Ok(())
+    }
+}
+
+impl Controller for ProcessController {
+    fn next_direction(&mut self, view: &GameView) -> Direction {
+        let current = view.snake().direction();
+        if self.disqualification.is_some() {
+            return current;
+        }
+        if self.send_state(view).is_err() {
+            self.disqualification = Some("bot exited".to_owned());
+            return current;
+        }
+
+        // the first reply also has to wait for the bot to start up
+        let mut deadline = Instant::now() + self.time_limit;
+        if !self.started {
+            deadline += STARTUP_TIME;
+            self.started = true;
+        }
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let reply = match self.replies.recv_timeout(timeout) {
+                Ok(reply) => reply,
+                Err(RecvTimeoutError::Timeout) => {
+                    self.timeouts += 1;
+                    return current;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.disqualification = Some("bot exited".to_owned());
+                    return current;
+                }
+            };
+            self.pending -= 1;
+
+            let direction = match reply.trim().to_lowercase().as_str() {
+                "up" => Direction::Up,
+                "down" => Direction::Down,
+                "left" => Direction::Left,
+                "right" => Direction::Right,
+                _ => {
+                    self.disqualification = Some(format!("invalid reply {reply:?}"));
+                    return current;
+                }
+            };
+            // replies that arrive too late still have to be valid,
+            // but only the one for the current step is used
+            if self.pending == 0 {
+                return direction;
+            }
+        }
+    }
+
+    fn timeouts(&self) -> u32 {
+        self.timeouts
+    }
+
+    fn disqualification(&self) -> Option<&str> {
+        self.disqualification.as_deref()
+    }
+}
+
+impl Drop for ProcessController {
+    fn drop(&mut self) {
+        // the bot may already be gone, so errors do not matter here
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
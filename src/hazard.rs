@@ -0,0 +1,108 @@
+use crate::{Board, Tile};
+use serde::{Deserialize, Serialize};
+
+// a tile moving over the board by itself, killing the snakes it touches;
+// it stays on the board like an obstacle, so no food is placed under it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hazard {
+    position: (usize, usize),
+    movement: Movement,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Movement {
+    // walks along the cells of the path and the same way back;
+    // `next` is the index of the cell it moves to next
+    Patrol {
+        path: Vec<(usize, usize)>,
+        next: usize,
+        forward: bool,
+    },
+    // keeps going by the offsets, -1, 0 or 1, turning back at the edges
+    // of the board and at everything fixed on it
+    Bounce {
+        dx: isize,
+        dy: isize,
+    },
+}
+
+impl Hazard {
+    // starting at the first cell of the path
+    pub fn patrol(path: Vec<(usize, usize)>) -> Self {
+        Self {
+            position: path[0],
+            movement: Movement::Patrol {
+                next: usize::from(path.len() > 1),
+                path,
+                forward: true,
+            },
+        }
+    }
+
+    pub fn bounce(position: (usize, usize), (dx, dy): (isize, isize)) -> Self {
+        Self {
+            position,
+            movement: Movement::Bounce { dx, dy },
+        }
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    // moves the hazard one tile on the board, crushing food in the way;
+    // it waits when the way is blocked, e.g. by another hazard
+    pub(crate) fn advance(&mut self, board: &mut Board) {
+        let (x, y) = self.position;
+        let open = |dx: isize, dy: isize| {
+            let cell = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+            let tile = board.get(cell.1)?.get(cell.0)?;
+            (!tile.is_fixed()).then_some(cell)
+        };
+        let target = match &mut self.movement {
+            Movement::Patrol {
+                path,
+                next,
+                forward,
+            } => {
+                let target = path[*next];
+                let (dx, dy) = (
+                    target.0 as isize - x as isize,
+                    target.1 as isize - y as isize,
+                );
+                if open(dx, dy).is_none() {
+                    return;
+                }
+                if (*forward && *next + 1 == path.len()) || (!*forward && *next == 0) {
+                    *forward = !*forward;
+                }
+                if path.len() > 1 {
+                    *next = if *forward { *next + 1 } else { *next - 1 };
+                }
+                target
+            }
+            Movement::Bounce { dx, dy } => {
+                if *dx != 0 && open(*dx, 0).is_none() {
+                    *dx = -*dx;
+                }
+                if *dy != 0 && open(0, *dy).is_none() {
+                    *dy = -*dy;
+                }
+                // running into a corner
+                if open(*dx, *dy).is_none() {
+                    *dx = -*dx;
+                    *dy = -*dy;
+                }
+                let Some(target) = open(*dx, *dy) else {
+                    return;
+                };
+                target
+            }
+        };
+
+        board[y][x] = Tile::Empty;
+        board[target.1][target.0] = Tile::Hazard;
+        self.position = target;
+    }
+}
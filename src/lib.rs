@@ -0,0 +1,1809 @@
+#[cfg(feature = "terminal")]
+use config::{Action, Key, KeyBindings};
+use controller::{Controller, GameView};
+#[cfg(feature = "terminal")]
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent},
+    style::{
+        Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+        StyledContent, Stylize,
+    },
+    terminal::{self, ClearType},
+    QueueableCommand, Result,
+};
+use hazard::Hazard;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use replay::Replay;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::AddAssign;
+#[cfg(feature = "terminal")]
+use std::{fmt::Write, path::Path, time::SystemTime};
+use std::{io::Write as IOWrite, ops::SubAssign, time::Duration};
+#[cfg(feature = "terminal")]
+use theme::Theme;
+
+pub const BOARD_WIDTH: usize = 50;
+pub const BOARD_HEIGHT: usize = 20;
+pub const STEP_LENGTH: u64 = 300;
+// the shortest snake has three tiles and needs one more to move into;
+// the largest boards still fit into memory many times over
+pub const MIN_BOARD_SIZE: usize = 4;
+pub const MAX_BOARD_SIZE: usize = 1000;
+// bumped whenever a change to the rules makes recorded games play out
+// differently, old replays cannot be watched anymore then
+pub const RULES_VERSION: u32 = 3;
+
+// random number generator for games that are recorded or saved; unlike
+// `SmallRng` its state can be saved and it is the same on every platform
+pub type GameRng = Xoshiro256PlusPlus;
+const MAX_FOOD_ON_BOARD: usize = 20;
+// how long a time attack lasts
+const TIME_ATTACK_LENGTH: Duration = Duration::from_secs(60);
+// in survival food goes bad after this many steps on average
+const FOOD_LIFETIME: u32 = 40;
+// and snakes lose a tile every this many steps
+const SHRINK_INTERVAL: u64 = 10;
+// hazards move every this many steps, slower than the snakes
+const HAZARD_INTERVAL: u64 = 2;
+// with power-ups on, one in this many pieces of food is one
+const POWER_UP_CHANCE: u32 = 8;
+// steps the ghost, magnet and slow-motion last
+const POWER_UP_LENGTH: u64 = 30;
+// tiles a snake loses by eating shrink
+const SHRINK_TILES: usize = 3;
+// the magnet pulls food at most this many tiles away from the head
+const MAGNET_RANGE: usize = 5;
+// slow-motion makes the steps this many times longer
+const SLOW_MOTION_FACTOR: u32 = 2;
+
+#[cfg(feature = "terminal")]
+pub mod campaign;
+#[cfg(feature = "terminal")]
+pub mod cast;
+#[cfg(feature = "terminal")]
+pub mod config;
+pub mod controller;
+#[cfg(feature = "terminal")]
+pub mod daily;
+pub mod env;
+pub mod external;
+pub mod hazard;
+#[cfg(feature = "terminal")]
+pub mod lockstep;
+#[cfg(feature = "terminal")]
+pub mod menu;
+#[cfg(feature = "terminal")]
+pub mod net;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "export")]
+pub mod render;
+pub mod replay;
+pub mod save;
+#[cfg(feature = "terminal")]
+pub mod scores;
+pub mod solver;
+pub mod svg;
+pub mod theme;
+pub mod tournament;
+#[cfg(feature = "terminal")]
+pub mod viewer;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    // direction of the step from one cell to an adjacent one,
+    // taking board wrapping into account
+    fn between(from: (usize, usize), to: (usize, usize), width: usize, height: usize) -> Self {
+        if to == ((from.0 + 1) % width, from.1) {
+            Direction::Right
+        } else if to == ((from.0 + width - 1) % width, from.1) {
+            Direction::Left
+        } else if to == (from.0, (from.1 + 1) % height) {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+
+    // the cell one step away in this direction, wrapping around the board
+    pub fn apply(self, (x, y): (usize, usize), width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Direction::Up => (x, (y + height - 1) % height),
+            Direction::Right => ((x + 1) % width, y),
+            Direction::Down => (x, (y + 1) % height),
+            Direction::Left => ((x + width - 1) % width, y),
+        }
+    }
+}
+
+// what happens to a snake leaving the board
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Boundary {
+    // it comes back in on the opposite side
+    #[default]
+    Wrap,
+    // it runs into a wall and dies
+    Walls,
+}
+
+impl Boundary {
+    pub const ALL: [Boundary; 2] = [Boundary::Wrap, Boundary::Walls];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Boundary::Wrap => "wrap",
+            Boundary::Walls => "walls",
+        }
+    }
+
+    // the cell one step away in the direction, None if it is behind a wall
+    pub fn apply(
+        self,
+        direction: Direction,
+        (x, y): (usize, usize),
+        width: usize,
+        height: usize,
+    ) -> Option<(usize, usize)> {
+        let blocked = match direction {
+            Direction::Up => y == 0,
+            Direction::Right => x == width - 1,
+            Direction::Down => y == height - 1,
+            Direction::Left => x == 0,
+        };
+        if self == Boundary::Walls && blocked {
+            None
+        } else {
+            Some(direction.apply((x, y), width, height))
+        }
+    }
+}
+
+// rule sets changing how a game is won or lost
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rules {
+    #[default]
+    Classic,
+    // as many points as possible until the time is up; the time is counted
+    // in steps of the normal length, so slow-motion makes it last longer
+    TimeAttack,
+    // nothing kills, snakes cross themselves and each other and just grow;
+    // the walls of the boundary are ignored too and the snakes wrap around
+    Zen,
+    // food goes bad and snakes keep shrinking, they starve
+    // when they would get shorter than three tiles
+    Survival,
+}
+
+impl Rules {
+    pub const ALL: [Rules; 4] = [
+        Rules::Classic,
+        Rules::TimeAttack,
+        Rules::Zen,
+        Rules::Survival,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rules::Classic => "classic",
+            Rules::TimeAttack => "time attack",
+            Rules::Zen => "zen",
+            Rules::Survival => "survival",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum BodyPartDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+    TopLeftCornerRight,
+    TopLeftCornerDown,
+    TopRightCornerLeft,
+    TopRightCornerDown,
+    BottomLeftCornerRight,
+    BottomLeftCornerUp,
+    BottomRightCornerLeft,
+    BottomRightCornerUp,
+}
+
+impl BodyPartDirection {
+    // body part entered moving `incoming` and left moving `outgoing`
+    fn from_turn(incoming: Direction, outgoing: Direction) -> Self {
+        match (incoming, outgoing) {
+            (Direction::Up, Direction::Up) => BodyPartDirection::Up,
+            (Direction::Down, Direction::Down) => BodyPartDirection::Down,
+            (Direction::Left, Direction::Left) => BodyPartDirection::Left,
+            (Direction::Right, Direction::Right) => BodyPartDirection::Right,
+            (Direction::Left, Direction::Up) => BodyPartDirection::BottomLeftCornerUp,
+            (Direction::Right, Direction::Up) => BodyPartDirection::BottomRightCornerUp,
+            (Direction::Left, Direction::Down) => BodyPartDirection::TopLeftCornerDown,
+            (Direction::Right, Direction::Down) => BodyPartDirection::TopRightCornerDown,
+            (Direction::Up, Direction::Left) => BodyPartDirection::TopRightCornerLeft,
+            (Direction::Down, Direction::Left) => BodyPartDirection::BottomRightCornerLeft,
+            (Direction::Up, Direction::Right) => BodyPartDirection::TopLeftCornerRight,
+            (Direction::Down, Direction::Right) => BodyPartDirection::BottomLeftCornerRight,
+            _ => unreachable!(),
+        }
+    }
+
+    // direction the body part was left in, toward the head
+    fn outgoing(self) -> Direction {
+        match self {
+            BodyPartDirection::BottomLeftCornerRight
+            | BodyPartDirection::Right
+            | BodyPartDirection::TopLeftCornerRight => Direction::Right,
+            BodyPartDirection::BottomLeftCornerUp
+            | BodyPartDirection::Up
+            | BodyPartDirection::BottomRightCornerUp => Direction::Up,
+            BodyPartDirection::Down
+            | BodyPartDirection::TopLeftCornerDown
+            | BodyPartDirection::TopRightCornerDown => Direction::Down,
+            BodyPartDirection::Left
+            | BodyPartDirection::TopRightCornerLeft
+            | BodyPartDirection::BottomRightCornerLeft => Direction::Left,
+        }
+    }
+}
+
+// how `SnakeGame::play` ended
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Exit {
+    GameOver,
+    Quit,
+    // the player wants to continue the game later
+    Save,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum GameState {
+    Running,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Tile {
+    Empty,
+    Food(FoodType),
+    // kills snakes running into it, like a wall
+    Obstacle,
+    // one of a pair, see `Portal`
+    Portal,
+    // see `Hazard`
+    Hazard,
+    SnakePart(SnakePart, bool),
+}
+
+impl Tile {
+    pub fn is_empty(&self) -> bool {
+        *self == Tile::Empty
+    }
+
+    pub fn has_food(&self) -> bool {
+        matches!(*self, Tile::Food(_))
+    }
+
+    // obstacles, portals and hazards stay on the board,
+    // the snakes are drawn around them
+    pub fn is_fixed(&self) -> bool {
+        matches!(*self, Tile::Obstacle | Tile::Portal | Tile::Hazard)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum FoodType {
+    Blob,
+    // does not make the snake grow, but has an effect on it
+    PowerUp(PowerUp),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUp {
+    // the snake moves through its own body for a while
+    Ghost,
+    // food near the head comes closer for a while
+    Magnet,
+    // the snake loses some tiles at once
+    Shrink,
+    // the game runs slower for a while
+    SlowMotion,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 4] = [
+        PowerUp::Ghost,
+        PowerUp::Magnet,
+        PowerUp::Shrink,
+        PowerUp::SlowMotion,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUp::Ghost => "ghost",
+            PowerUp::Magnet => "magnet",
+            PowerUp::Shrink => "shrink",
+            PowerUp::SlowMotion => "slow motion",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum SnakePart {
+    Head(Direction),
+    Body(BodyPartDirection),
+    Tail(Direction),
+}
+
+pub type Board = Vec<Vec<Tile>>;
+
+// the error to show for a board that is too small or too large
+pub fn check_board_size(width: usize, height: usize) -> std::result::Result<(), String> {
+    let sizes = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+    if sizes.contains(&width) && sizes.contains(&height) {
+        Ok(())
+    } else {
+        Err(format!(
+            "the board must be between {MIN_BOARD_SIZE}x{MIN_BOARD_SIZE} and \
+             {MAX_BOARD_SIZE}x{MAX_BOARD_SIZE}, not {width}x{height}"
+        ))
+    }
+}
+
+// two tiles linked to each other: a head moving onto one of them comes
+// out of the other and goes on in the same direction, so the tiles of a
+// snake are not always next to each other on the board
+pub type Portal = [(usize, usize); 2];
+
+// one step from the coordinate toward the other one
+pub(crate) fn step_toward(from: usize, to: usize) -> usize {
+    match from.cmp(&to) {
+        std::cmp::Ordering::Less => from + 1,
+        std::cmp::Ordering::Equal => from,
+        std::cmp::Ordering::Greater => from - 1,
+    }
+}
+
+// the tile one step away in the direction, through the portal there is
+// one there; None if there is a wall in the way
+pub fn next_position(
+    boundary: Boundary,
+    portals: &[Portal],
+    direction: Direction,
+    position: (usize, usize),
+    width: usize,
+    height: usize,
+) -> Option<(usize, usize)> {
+    let next = boundary.apply(direction, position, width, height)?;
+    match portals.iter().find(|portal| portal.contains(&next)) {
+        Some(&[a, b]) => boundary.apply(direction, if next == a { b } else { a }, width, height),
+        None => Some(next),
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct SnakeTile {
+    x: Wrap,
+    y: Wrap,
+    snake_tile_type: SnakePart,
+    eating: bool,
+}
+
+impl SnakeTile {
+    // (x, y) with (0, 0) in the top left corner
+    pub fn position(&self) -> (usize, usize) {
+        (self.x.number, self.y.number)
+    }
+
+    pub fn part(&self) -> SnakePart {
+        self.snake_tile_type
+    }
+
+    pub fn is_eating(&self) -> bool {
+        self.eating
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Wrap {
+    modulus: usize,
+    number: usize,
+}
+
+impl Wrap {
+    fn new(number: usize, modulus: usize) -> Self {
+        Self {
+            modulus,
+            number: number % modulus,
+        }
+    }
+
+    // increment the number, wrap if needed
+    fn inc(&mut self) {
+        self.number = if self.number == self.modulus - 1 {
+            0
+        } else {
+            self.number + 1
+        }
+    }
+
+    // decrement the number, wrap if needed
+    fn dec(&mut self) {
+        self.number = if self.number == 0 {
+            self.modulus - 1
+        } else {
+            self.number - 1
+        }
+    }
+}
+
+impl AddAssign<usize> for Wrap {
+    fn add_assign(&mut self, rhs: usize) {
+        self.number = if self.number + rhs == self.modulus {
+            0
+        } else {
+            (self.number + rhs) % self.modulus
+        }
+    }
+}
+
+impl SubAssign<usize> for Wrap {
+    fn sub_assign(&mut self, rhs: usize) {
+        self.number = if self.number as isize - rhs as isize == -1 {
+            self.modulus - 1
+        } else {
+            self.number - rhs
+        }
+    }
+}
+
+impl From<Wrap> for usize {
+    fn from(w: Wrap) -> Self {
+        w.number
+    }
+}
+
+// TODO: move game structs and logic to module to make internals private
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snake {
+    body: Vec<SnakeTile>,
+    score: u32,
+    alive: bool,
+    // the power-ups working on the snake and the steps they have left
+    #[serde(default)]
+    effects: Vec<(PowerUp, u64)>,
+}
+
+impl Snake {
+    // straight snake heading right; snakes are spread
+    // evenly over the rows so that they do not overlap
+    pub fn spawn(index: usize, count: usize, board_width: usize, board_height: usize) -> Self {
+        let y = (index + 1) * board_height / (count + 1);
+        // at least three tiles on the smallest boards
+        let length = 6.min(board_width - 1);
+        let cells: Vec<_> = (1..=length).rev().map(|x| (x, y)).collect();
+
+        Snake::from_cells(&cells, board_width, board_height)
+    }
+
+    // builds a snake occupying the given cells, head first;
+    // consecutive cells must be adjacent on the board
+    pub fn from_cells(cells: &[(usize, usize)], board_width: usize, board_height: usize) -> Self {
+        let direction =
+            |from: (usize, usize), to| Direction::between(from, to, board_width, board_height);
+        let last = cells.len() - 1;
+        let body = cells
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| {
+                let snake_tile_type = if i == 0 {
+                    SnakePart::Head(direction(cells[1], cells[0]))
+                } else if i == last {
+                    SnakePart::Tail(direction(cells[i], cells[i - 1]))
+                } else {
+                    SnakePart::Body(BodyPartDirection::from_turn(
+                        direction(cells[i + 1], cells[i]),
+                        direction(cells[i], cells[i - 1]),
+                    ))
+                };
+                SnakeTile {
+                    x: Wrap::new(x, board_width),
+                    y: Wrap::new(y, board_height),
+                    snake_tile_type,
+                    eating: false,
+                }
+            })
+            .collect();
+
+        Snake {
+            body,
+            score: 0,
+            alive: true,
+            effects: Vec::new(),
+        }
+    }
+
+    // a snake always has at least a head and a tail
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.body.len()
+    }
+
+    pub fn head(&self) -> &SnakeTile {
+        &self.body[0]
+    }
+
+    pub fn last(&self) -> &SnakeTile {
+        self.body.last().unwrap()
+    }
+
+    pub fn direction(&self) -> Direction {
+        match self.head().snake_tile_type {
+            SnakePart::Head(direction) => direction,
+            _ => unreachable!(),
+        }
+    }
+
+    // directions reversing the snake are ignored
+    pub fn turn(&mut self, direction: Direction) {
+        if direction != self.direction().opposite() {
+            self.head_mut().snake_tile_type = SnakePart::Head(direction);
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    pub fn head_mut(&mut self) -> &mut SnakeTile {
+        &mut self.body[0]
+    }
+
+    pub fn whole_snake(&self) -> &Vec<SnakeTile> {
+        &self.body
+    }
+
+    pub fn effects(&self) -> &[(PowerUp, u64)] {
+        &self.effects
+    }
+
+    pub fn has_effect(&self, power_up: PowerUp) -> bool {
+        self.effects.iter().any(|&(effect, _)| effect == power_up)
+    }
+
+    // the tile before the tail becomes the tail
+    fn drop_tail(&mut self) {
+        self.body.pop();
+        let last = self.body.last_mut().unwrap();
+        if let SnakePart::Body(direction) = last.snake_tile_type {
+            last.snake_tile_type = SnakePart::Tail(direction.outgoing());
+        }
+    }
+}
+
+// everything about a game that changes while it is played,
+// to go back to that point later
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot<R> {
+    board: Board,
+    snakes: Vec<Snake>,
+    rng: R,
+    state: GameState,
+    tick: u64,
+    replay: Replay,
+    // where they are now, the replay has where they started
+    #[serde(default)]
+    hazards: Vec<Hazard>,
+}
+
+impl<R> Snapshot<R> {
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.replay.boundary
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.replay.rules
+    }
+}
+
+pub struct SnakeGame<R: SeedableRng + Rng, W: IOWrite> {
+    // only drawn to with the terminal
+    #[cfg_attr(not(feature = "terminal"), allow(dead_code))]
+    out: W,
+    board: Board,
+    board_width: usize,
+    board_height: usize,
+    snakes: Vec<Snake>,
+    // controllers[i] drives snakes[i]
+    controllers: Vec<Box<dyn Controller>>,
+    rng: R,
+    state: GameState,
+    tick: u64,
+    step_length: Duration,
+    boundary: Boundary,
+    rules: Rules,
+    // the length a snake has to reach to win
+    target: Option<usize>,
+    hazards: Vec<Hazard>,
+    // whether some of the food is power-ups
+    power_ups: bool,
+    // the game so far, to be watched again later
+    replay: Replay,
+    // clients watching the game over the network
+    #[cfg(feature = "terminal")]
+    spectators: Option<net::Spectators>,
+    #[cfg(feature = "terminal")]
+    keys: KeyBindings,
+    // colours of the board, the terminal's own ones if None
+    #[cfg(feature = "terminal")]
+    theme: Option<Theme>,
+}
+
+impl<R: SeedableRng + Rng, W: IOWrite> SnakeGame<R, W> {
+    pub fn new(board_width: usize, board_height: usize, out: W) -> Self {
+        // a random seed rather than entropy, so that the game can be replayed
+        Self::with_seed(board_width, board_height, out, rand::random())
+    }
+
+    // games with the same seed and the same moves play out the same way;
+    // sizes given by the user are checked with `check_board_size` first
+    pub fn with_seed(board_width: usize, board_height: usize, out: W, seed: u64) -> Self {
+        if let Err(err) = check_board_size(board_width, board_height) {
+            panic!("{err}");
+        }
+        let step_length = Duration::from_millis(STEP_LENGTH);
+
+        SnakeGame {
+            out,
+            board: vec![vec![Tile::Empty; board_width]; board_height],
+            board_width,
+            board_height,
+            snakes: Vec::new(),
+            controllers: Vec::new(),
+            rng: R::seed_from_u64(seed),
+            state: GameState::Running,
+            tick: 0,
+            step_length,
+            boundary: Boundary::default(),
+            rules: Rules::default(),
+            target: None,
+            hazards: Vec::new(),
+            power_ups: false,
+            replay: Replay::new(seed, board_width, board_height, step_length),
+            #[cfg(feature = "terminal")]
+            spectators: None,
+            #[cfg(feature = "terminal")]
+            keys: KeyBindings::default(),
+            #[cfg(feature = "terminal")]
+            theme: None,
+        }
+    }
+
+    pub fn add_snake(&mut self, snake: Snake, controller: Box<dyn Controller>) {
+        self.replay.add_snake(&snake);
+        self.snakes.push(snake);
+        self.controllers.push(controller);
+    }
+
+    pub fn set_step_length(&mut self, step_length: Duration) {
+        self.step_length = step_length;
+        self.replay.step_length = step_length.as_millis() as u64;
+    }
+
+    // set before the game starts, like the step length
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+        self.replay.boundary = boundary;
+    }
+
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+        self.replay.rules = rules;
+    }
+
+    pub fn set_power_ups(&mut self, power_ups: bool) {
+        self.power_ups = power_ups;
+        self.replay.power_ups = power_ups;
+    }
+
+    pub fn set_target(&mut self, target: Option<usize>) {
+        self.target = target;
+        self.replay.target = target;
+    }
+
+    // before the snakes and the food are added
+    pub fn add_obstacles(&mut self, obstacles: &[(usize, usize)]) {
+        for &(x, y) in obstacles {
+            self.board[y][x] = Tile::Obstacle;
+        }
+        self.replay.obstacles.extend_from_slice(obstacles);
+    }
+
+    // also before the snakes and the food
+    pub fn add_portals(&mut self, portals: &[Portal]) {
+        for &(x, y) in portals.iter().flatten() {
+            self.board[y][x] = Tile::Portal;
+        }
+        self.replay.portals.extend_from_slice(portals);
+    }
+
+    // also before the snakes and the food
+    pub fn add_hazards(&mut self, hazards: &[Hazard]) {
+        for hazard in hazards {
+            let (x, y) = hazard.position();
+            self.board[y][x] = Tile::Hazard;
+        }
+        self.hazards.extend_from_slice(hazards);
+        self.replay.hazards.extend_from_slice(hazards);
+    }
+
+    pub fn hazards(&self) -> &[Hazard] {
+        &self.hazards
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    pub fn target(&self) -> Option<usize> {
+        self.target
+    }
+
+    pub fn power_ups(&self) -> bool {
+        self.power_ups
+    }
+
+    // slow-motion of any snake makes the steps of everyone longer
+    pub fn current_step_length(&self) -> Duration {
+        let slow_motion = self
+            .snakes
+            .iter()
+            .any(|snake| snake.alive && snake.has_effect(PowerUp::SlowMotion));
+        if slow_motion {
+            self.step_length * SLOW_MOTION_FACTOR
+        } else {
+            self.step_length
+        }
+    }
+
+    // the step a time attack ends with, as many steps as a minute has at
+    // the normal step length
+    pub fn time_limit(&self) -> u64 {
+        (TIME_ATTACK_LENGTH.as_millis() / self.step_length.as_millis().max(1)) as u64
+    }
+
+    pub fn snakes(&self) -> &[Snake] {
+        &self.snakes
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    // controllers keep their own state, so going back in time only works
+    // with controllers that do not depend on it, like the ones of a replay
+    pub fn snapshot(&self) -> Snapshot<R>
+    where
+        R: Clone,
+    {
+        Snapshot {
+            board: self.board.clone(),
+            snakes: self.snakes.clone(),
+            rng: self.rng.clone(),
+            state: self.state,
+            tick: self.tick,
+            replay: self.replay.clone(),
+            hazards: self.hazards.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot<R>)
+    where
+        R: Clone,
+    {
+        self.board = snapshot.board.clone();
+        self.snakes = snapshot.snakes.clone();
+        self.rng = snapshot.rng.clone();
+        self.state = snapshot.state;
+        self.tick = snapshot.tick;
+        self.replay = snapshot.replay.clone();
+        self.hazards = snapshot.hazards.clone();
+        // the rules of the game are kept with its recording
+        self.boundary = snapshot.replay.boundary;
+        self.rules = snapshot.replay.rules;
+        self.target = snapshot.replay.target;
+        self.power_ups = snapshot.replay.power_ups;
+    }
+
+    // lets the controllers steer, moves the hazards and the snakes one tile
+    // forward and spawns new food; snakes running into a snake, an obstacle
+    // or a hazard die and the game ends when all of them are dead, the board
+    // is full or a snake reached the target length, or with time attack
+    // rules when the time is up
+    fn step(&mut self) {
+        self.tick += 1;
+        self.wear_off();
+        for (i, controller) in self.controllers.iter_mut().enumerate() {
+            if !self.snakes[i].alive {
+                continue;
+            }
+            let view = GameView {
+                tick: self.tick,
+                board: &self.board,
+                snakes: &self.snakes,
+                snake: i,
+                boundary: self.boundary,
+                portals: &self.replay.portals,
+            };
+            let direction = controller.next_direction(&view);
+            if controller.disqualification().is_some() {
+                self.snakes[i].alive = false;
+            } else {
+                self.snakes[i].turn(direction);
+            }
+            self.replay.record(i, &self.snakes[i]);
+        }
+        if self.tick.is_multiple_of(HAZARD_INTERVAL) {
+            self.move_hazards();
+        }
+
+        let colliding: Vec<_> = (0..self.snakes.len())
+            .map(|i| self.snakes[i].alive && self.rules != Rules::Zen && self.is_colliding(i))
+            .collect();
+        for (snake, colliding) in self.snakes.iter_mut().zip(colliding) {
+            if colliding {
+                snake.alive = false;
+            }
+        }
+
+        for i in 0..self.snakes.len() {
+            if !self.snakes[i].alive {
+                continue;
+            }
+            self.move_snake_experimental(i);
+            if self.snakes[i].head().eating {
+                self.snakes[i].score += 1;
+            }
+            self.pick_up(i);
+        }
+        self.pull_food();
+        if self.rules == Rules::Survival {
+            self.decay();
+        }
+
+        if self.snakes.iter().all(|snake| !snake.alive)
+            || (self.rules == Rules::TimeAttack && self.tick >= self.time_limit())
+        {
+            self.state = GameState::Lost;
+            return;
+        }
+        let snake_tiles: usize = self
+            .snakes
+            .iter()
+            .filter(|snake| snake.alive)
+            .map(|snake| snake.len())
+            .sum();
+        // zen snakes overlap, so they can get even longer
+        let free_tiles = self.board_width * self.board_height
+            - self.replay.obstacles.len()
+            - 2 * self.replay.portals.len()
+            - self.hazards.len();
+        let reached_target = self.target.is_some_and(|target| {
+            self.snakes
+                .iter()
+                .any(|snake| snake.alive && snake.len() >= target)
+        });
+        if snake_tiles >= free_tiles || reached_target {
+            self.state = GameState::Won;
+            return;
+        }
+
+        self.spawn_food();
+    }
+
+    // hazards moving onto a snake kill it, snakes moving onto
+    // one are caught by the collision check
+    fn move_hazards(&mut self) {
+        for hazard in &mut self.hazards {
+            hazard.advance(&mut self.board);
+        }
+        if self.rules == Rules::Zen {
+            return;
+        }
+        for snake in self.snakes.iter_mut().filter(|snake| snake.alive) {
+            if snake
+                .body
+                .iter()
+                .any(|tile| self.board[usize::from(tile.y)][usize::from(tile.x)] == Tile::Hazard)
+            {
+                snake.alive = false;
+            }
+        }
+    }
+
+    // counts down the steps left of the power-ups; a ghost snake that still
+    // lies over itself when the ghost wears off dies, outside of zen
+    fn wear_off(&mut self) {
+        for snake in &mut self.snakes {
+            for (_, steps) in &mut snake.effects {
+                *steps -= 1;
+            }
+            let ghost = snake.has_effect(PowerUp::Ghost);
+            snake.effects.retain(|&(_, steps)| steps > 0);
+            if ghost && !snake.has_effect(PowerUp::Ghost) && self.rules != Rules::Zen {
+                let mut seen = HashSet::new();
+                if snake.body.iter().any(|tile| !seen.insert(tile.position())) {
+                    snake.alive = false;
+                }
+            }
+        }
+    }
+
+    // a power-up under the head takes effect; eating one that is
+    // still working starts it over
+    fn pick_up(&mut self, index: usize) {
+        let (x, y) = self.snakes[index].head().position();
+        let Tile::Food(FoodType::PowerUp(power_up)) = self.board[y][x] else {
+            return;
+        };
+        self.board[y][x] = Tile::Empty;
+        let snake = &mut self.snakes[index];
+        if power_up == PowerUp::Shrink {
+            for _ in 0..SHRINK_TILES {
+                if snake.len() <= 3 {
+                    break;
+                }
+                snake.drop_tail();
+            }
+        } else {
+            snake.effects.retain(|&(effect, _)| effect != power_up);
+            snake.effects.push((power_up, POWER_UP_LENGTH));
+        }
+    }
+
+    // magnets pull the food around the head of their snake one tile
+    // closer every step, along the longer way to the head
+    fn pull_food(&mut self) {
+        let heads: Vec<_> = self
+            .snakes
+            .iter()
+            .filter(|snake| snake.alive && snake.has_effect(PowerUp::Magnet))
+            .map(|snake| snake.head().position())
+            .collect();
+        if heads.is_empty() {
+            return;
+        }
+
+        self.add_snake_to_board();
+        for (head_x, head_y) in heads {
+            let near = |x: usize, y: usize| {
+                x.abs_diff(head_x) <= MAGNET_RANGE && y.abs_diff(head_y) <= MAGNET_RANGE
+            };
+            let mut food: Vec<_> = self
+                .board
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, tile)| (x, y, tile)))
+                .filter(|&(x, y, tile)| tile.has_food() && near(x, y))
+                .map(|(x, y, _)| (x, y))
+                .collect();
+            // the closest first, so that they do not block the others
+            food.sort_by_key(|&(x, y)| x.abs_diff(head_x) + y.abs_diff(head_y));
+            for (x, y) in food {
+                let (next_x, next_y) = if x.abs_diff(head_x) >= y.abs_diff(head_y) {
+                    (step_toward(x, head_x), y)
+                } else {
+                    (x, step_toward(y, head_y))
+                };
+                if self.board[next_y][next_x].is_empty() {
+                    self.board[next_y][next_x] = self.board[y][x];
+                    self.board[y][x] = Tile::Empty;
+                }
+            }
+        }
+        self.remove_snake_from_board();
+    }
+
+    // survival rules: some food goes bad and every SHRINK_INTERVAL steps
+    // the snakes lose their tail, or the growth still being swallowed
+    fn decay(&mut self) {
+        for tile in self.board.iter_mut().flatten() {
+            if tile.has_food() && self.rng.gen_ratio(1, FOOD_LIFETIME) {
+                *tile = Tile::Empty;
+            }
+        }
+
+        if !self.tick.is_multiple_of(SHRINK_INTERVAL) {
+            return;
+        }
+        for snake in self.snakes.iter_mut().filter(|snake| snake.alive) {
+            let tail = snake.body.last_mut().unwrap();
+            if tail.eating {
+                tail.eating = false;
+            } else if snake.len() <= 3 {
+                snake.alive = false;
+            } else {
+                snake.drop_tail();
+            }
+        }
+    }
+
+    // food must not be placed under the snakes,
+    // so they are put on the board while spawning;
+    // new games call it once after adding the snakes
+    pub fn spawn_food(&mut self) {
+        self.add_snake_to_board();
+        for _ in 0..5 {
+            self.add_food();
+        }
+        self.remove_snake_from_board();
+    }
+
+    // None if the snake is heading into a wall
+    fn next_head_position(&self, index: usize) -> Option<(usize, usize)> {
+        let snake = &self.snakes[index];
+
+        next_position(
+            self.boundary,
+            &self.replay.portals,
+            snake.direction(),
+            snake.head().position(),
+            self.board_width,
+            self.board_height,
+        )
+    }
+
+    // checks whether the next move would run the head into a wall, a snake
+    // or into another head moving to the same tile
+    fn is_colliding(&self, index: usize) -> bool {
+        let Some(next) = self.next_head_position(index) else {
+            return true;
+        };
+
+        !self.is_safe(index, next)
+            || (0..self.snakes.len()).any(|i| {
+                i != index && self.snakes[i].alive && self.next_head_position(i) == Some(next)
+            })
+    }
+
+    // whether the head of the snake can move onto the tile this step without
+    // running into an obstacle or a snake, other than itself as a ghost;
+    // tails are only in the way if they are not going to move. A portal is
+    // only ever moved onto when it comes right after another one, which is
+    // in the way like an obstacle
+    fn is_safe(&self, index: usize, position: (usize, usize)) -> bool {
+        let in_the_way = |(i, snake): &(usize, &Snake)| {
+            snake.alive && !(*i == index && snake.has_effect(PowerUp::Ghost))
+        };
+        !self.board[position.1][position.0].is_fixed()
+            && !self
+                .snakes
+                .iter()
+                .enumerate()
+                .filter(in_the_way)
+                .any(|(_, snake)| {
+                    let body = snake.whole_snake();
+                    let tail = snake.last();
+                    body[..body.len() - 1]
+                        .iter()
+                        .any(|tile| tile.position() == position)
+                        || (tail.eating && tail.position() == position)
+                })
+    }
+
+    // zen snakes go through obstacles, which stay on the board
+    // like the portals
+    fn add_snake_to_board(&mut self) {
+        let snakes = self.snakes.iter().filter(|snake| snake.alive);
+        for tile in snakes.flat_map(|snake| snake.whole_snake()) {
+            let board_tile = &mut self.board[usize::from(tile.y)][usize::from(tile.x)];
+            if !board_tile.is_fixed() {
+                *board_tile = Tile::SnakePart(tile.snake_tile_type, tile.eating);
+            }
+        }
+    }
+
+    fn remove_snake_from_board(&mut self) {
+        let snakes = self.snakes.iter().filter(|snake| snake.alive);
+        for tile in snakes.flat_map(|snake| snake.whole_snake()) {
+            let board_tile = &mut self.board[usize::from(tile.y)][usize::from(tile.x)];
+            if !board_tile.is_fixed() {
+                *board_tile = Tile::Empty;
+            }
+        }
+    }
+
+    fn move_snake_experimental(&mut self, index: usize) {
+        let snake = &mut self.snakes[index];
+
+        // move head
+        let SnakeTile {
+            mut x,
+            mut y,
+            snake_tile_type,
+            eating: mut head_eating,
+        } = snake.body[0];
+
+        let old_head_x = x;
+        let old_head_y = y;
+        let head_direction = match snake_tile_type {
+            SnakePart::Head(direction) => match direction {
+                Direction::Up => {
+                    y.dec();
+                    Direction::Up
+                }
+                Direction::Right => {
+                    x.inc();
+                    Direction::Right
+                }
+                Direction::Down => {
+                    y.inc();
+                    Direction::Down
+                }
+                Direction::Left => {
+                    x.dec();
+                    Direction::Left
+                }
+            },
+            _ => unreachable!(),
+        };
+        // the head jumps over the portal it moved onto to the tile after
+        // the other one; the snake keeps its direction, so the tiles of
+        // the body still fit together even though they are apart
+        if let Some(&[a, b]) = self
+            .replay
+            .portals
+            .iter()
+            .find(|portal| portal.contains(&(usize::from(x), usize::from(y))))
+        {
+            let exit = if (usize::from(x), usize::from(y)) == a {
+                b
+            } else {
+                a
+            };
+            let (exit_x, exit_y) = head_direction.apply(exit, self.board_width, self.board_height);
+            x = Wrap::new(exit_x, self.board_width);
+            y = Wrap::new(exit_y, self.board_height);
+        }
+        let old_head_eating = head_eating;
+        // power-ups are picked up afterwards, they do not make it grow
+        head_eating = self.board[usize::from(y)][usize::from(x)] == Tile::Food(FoodType::Blob);
+
+        // push all snake tiles forward in snake vec
+        snake.body.rotate_right(1);
+
+        // copy tail
+        let tail = snake.body[0];
+        if tail.eating {
+            // push tail to the end
+            snake.body.push(SnakeTile {
+                eating: false,
+                ..tail
+            });
+        } else {
+            // tail replaces the last tile
+            let last = snake.body.last().unwrap();
+            let direction = match last.snake_tile_type {
+                SnakePart::Body(direction) => direction.outgoing(),
+                _ => unreachable!(),
+            };
+
+            *snake.body.last_mut().unwrap() = SnakeTile {
+                x: last.x,
+                y: last.y,
+                snake_tile_type: SnakePart::Tail(direction),
+                eating: last.eating,
+            };
+        }
+
+        // move head to the start
+        snake.body[0] = SnakeTile {
+            x,
+            y,
+            snake_tile_type,
+            eating: head_eating,
+        };
+
+        // add tile after the head that connects the head to the body,
+        // bent by how the snake got to the old head and where it goes now;
+        // the tile after it is the tail on a snake of three tiles
+        let incoming = match snake.body[2].snake_tile_type {
+            SnakePart::Body(direction) => direction.outgoing(),
+            SnakePart::Tail(direction) => direction,
+            SnakePart::Head(_) => unreachable!(),
+        };
+        let direction = BodyPartDirection::from_turn(incoming, head_direction);
+
+        snake.body[1] = SnakeTile {
+            x: old_head_x,
+            y: old_head_y,
+            snake_tile_type: SnakePart::Body(direction),
+            eating: old_head_eating,
+        };
+    }
+
+    // adds one food particle at random location
+    // food is only added to empty tile
+    fn add_food(&mut self) {
+        if self.count_food_on_board() >= MAX_FOOD_ON_BOARD || self.is_board_full() {
+            return;
+        }
+
+        let height = self.board_height;
+        let width = self.board_width;
+        // sampled as u32, a usize would take a different number
+        // of random bits on 32 bit platforms like wasm
+        let mut position = || {
+            (
+                self.rng.gen_range(0..width as u32) as usize,
+                self.rng.gen_range(0..height as u32) as usize,
+            )
+        };
+        let (mut x, mut y) = position();
+        while self.board[y][x] != Tile::Empty {
+            (x, y) = position();
+        }
+
+        let food = if self.power_ups && self.rng.gen_ratio(1, POWER_UP_CHANCE) {
+            let power_ups = PowerUp::ALL.len() as u32;
+            FoodType::PowerUp(PowerUp::ALL[self.rng.gen_range(0..power_ups) as usize])
+        } else {
+            FoodType::Blob
+        };
+        self.board[y][x] = Tile::Food(food);
+    }
+
+    fn count_food_on_board(&self) -> usize {
+        self.board
+            .iter()
+            .flatten()
+            .filter(|tile| tile.has_food())
+            .count()
+    }
+
+    fn is_board_full(&self) -> bool {
+        !self.board.iter().flatten().any(|tile| tile.is_empty())
+    }
+}
+
+// drawing and keyboard input, builds without a terminal (e.g. for wasm)
+// only have the simulation
+#[cfg(feature = "terminal")]
+impl<R: SeedableRng + Rng, W: IOWrite> SnakeGame<R, W> {
+    // keys for quitting, saving and the like; the keys steering the
+    // snakes belong to their keyboard controllers
+    pub fn set_key_bindings(&mut self, keys: KeyBindings) {
+        self.keys = keys;
+    }
+
+    pub fn set_theme(&mut self, theme: Option<Theme>) {
+        self.theme = theme;
+    }
+
+    pub fn set_up_screen(&mut self) -> Result<()> {
+        self.out
+            .queue(cursor::Hide)?
+            .queue(terminal::EnterAlternateScreen)?
+            .queue(cursor::MoveTo(0, 0))?
+            .flush()?;
+        terminal::enable_raw_mode()?;
+
+        Ok(())
+    }
+
+    pub fn tear_down_screen(&mut self) -> Result<()> {
+        terminal::disable_raw_mode()?;
+        self.out
+            .queue(cursor::Show)?
+            .queue(terminal::LeaveAlternateScreen)?
+            .flush()?;
+
+        Ok(())
+    }
+
+    pub fn play(&mut self) -> Result<Exit> {
+        let mut timer;
+        let mut step_time = Duration::ZERO;
+
+        'game: loop {
+            timer = SystemTime::now();
+            let state = match self.state {
+                GameState::Running => "",
+                GameState::Won if self.target.is_some() => {
+                    "\n\rYou reached the target length, you won! Press any key..."
+                }
+                GameState::Won => "\n\rThe board is full, you won! Press any key...",
+                GameState::Lost if self.snakes.iter().any(|snake| snake.alive) => {
+                    "\n\rTime is up! Press any key..."
+                }
+                GameState::Lost => "\n\rGame over! Press any key...",
+            };
+            let snakes = self.scoreboard();
+            let rules = self.rules_status();
+            self.add_snake_to_board();
+            self.draw(&format!(
+                "Press {} for the controls\n\r\
+                 step time: {} us{rules}{snakes}{state}",
+                self.keys.describe(Action::Controls),
+                step_time.as_micros(),
+            ))?;
+            self.remove_snake_from_board();
+            self.update_spectators();
+            if self.state != GameState::Running {
+                while !matches!(event::read()?, Event::Key(_)) {}
+                return Ok(Exit::GameOver);
+            }
+            step_time = timer.elapsed().unwrap();
+            std::thread::sleep(self.current_step_length());
+
+            while event::poll(Duration::ZERO /*from_millis(10)*/)? {
+                match event::read()? {
+                    Event::Key(KeyEvent { code: key, .. }) => {
+                        if let KeyCode::Char(c) = key {
+                            // \r - return to line start
+                            // \n - start a new line
+                            print!("\n\rinput: {c}\n\r");
+                        }
+                        match self.keys.action(key) {
+                            Some(Action::Quit) => break 'game,
+                            Some(Action::Save) => return Ok(Exit::Save),
+                            Some(Action::Snapshot) => {
+                                let theme = self.theme.clone().unwrap_or_default();
+                                match svg::save(self, &theme, Path::new(".")) {
+                                    Ok(path) => print!("board saved to {}\n\r", path.display()),
+                                    Err(e) => print!("could not save the board: {e}\n\r"),
+                                }
+                                std::thread::sleep(Duration::from_secs(1));
+                                continue;
+                            }
+                            Some(Action::Controls) => {
+                                self.show_controls()?;
+                                continue;
+                            }
+                            _ => {}
+                        }
+                        if !self
+                            .controllers
+                            .iter_mut()
+                            .any(|controller| controller.handle_key(key))
+                        {
+                            print!("\n\rIgnored user input.\n\r");
+                            std::thread::sleep(Duration::from_secs(1));
+                        }
+                    }
+                    Event::Resize(x, y) => {
+                        print!("new terminal size: {x}, {y}\n\r");
+                        std::thread::sleep(Duration::from_secs(1));
+                    }
+                    _ => unreachable!(),
+                }
+            }
+
+            self.step();
+        }
+
+        Ok(Exit::Quit)
+    }
+
+    // how the game is going by its rules, like the time left
+    fn rules_status(&self) -> String {
+        if let Some(target) = self.target {
+            return format!("\n\rreach length {target} to win");
+        }
+        match self.rules {
+            Rules::Classic => String::new(),
+            Rules::TimeAttack => {
+                let steps = self.time_limit().saturating_sub(self.tick);
+                let left = self.step_length * steps as u32;
+                format!("\n\rtime attack, {} s left", left.as_secs())
+            }
+            Rules::Zen => "\n\rzen, nothing can go wrong".to_owned(),
+            Rules::Survival => format!(
+                "\n\rsurvival, shrinking in {} steps",
+                SHRINK_INTERVAL - self.tick % SHRINK_INTERVAL
+            ),
+        }
+    }
+
+    // one line per snake with its length, score and fate
+    fn scoreboard(&self) -> String {
+        let mut text = String::new();
+        for (i, (snake, controller)) in self.snakes.iter().zip(&self.controllers).enumerate() {
+            write!(
+                &mut text,
+                "\n\rsnake {}: length {}, score {}",
+                i + 1,
+                snake.len(),
+                snake.score
+            )
+            .unwrap();
+            if controller.timeouts() > 0 {
+                write!(&mut text, ", {} timeouts", controller.timeouts()).unwrap();
+            }
+            for (power_up, steps) in snake.effects.iter().filter(|_| snake.alive) {
+                write!(&mut text, ", {} for {steps} steps", power_up.name()).unwrap();
+            }
+            if !snake.alive {
+                match controller.disqualification() {
+                    Some(reason) => write!(&mut text, " (disqualified: {reason})").unwrap(),
+                    None => text.push_str(" (dead)"),
+                }
+            }
+        }
+
+        text
+    }
+
+    fn draw(&mut self, additional_text: &str) -> Result<()> {
+        let width = self.board_width;
+
+        // top line of the board, walls are drawn solid
+        let (top, side, bottom) = match self.boundary {
+            Boundary::Wrap => (
+                "╔".to_owned() + &"═".repeat(width) + "╗",
+                '║',
+                "╚".to_owned() + &"═".repeat(width) + "╝",
+            ),
+            Boundary::Walls => ("█".repeat(width + 2), '█', "█".repeat(width + 2)),
+        };
+        self.out
+            .queue(terminal::Clear(ClearType::All))?
+            .queue(cursor::MoveTo(0, 0))?
+            .queue(Print(format!("{top}\n\r")))?;
+
+        // which snake every tile belongs to, for its colour
+        let mut owners = vec![vec![0; width]; self.board_height];
+        for (i, snake) in self.snakes.iter().enumerate() {
+            for tile in snake.whole_snake().iter().filter(|_| snake.alive) {
+                owners[usize::from(tile.y)][usize::from(tile.x)] = i;
+            }
+        }
+
+        for (y, row) in self.board.iter().enumerate() {
+            let Some(theme) = &self.theme else {
+                self.out.queue(Print(format!(
+                    "{side}{}{side}\n\r",
+                    row.iter()
+                        .fold(String::with_capacity(width), |mut line, tile| {
+                            write!(&mut line, "{}", styled_char(tile)).unwrap();
+                            line
+                        })
+                )))?;
+                continue;
+            };
+
+            self.out
+                .queue(Print(side))?
+                .queue(SetBackgroundColor(rgb(theme.background)))?;
+            for (x, tile) in row.iter().enumerate() {
+                let color = match *tile {
+                    Tile::Empty => None,
+                    Tile::Food(FoodType::Blob) => Some(theme.food),
+                    Tile::Food(FoodType::PowerUp(_)) => Some(theme.power_up),
+                    Tile::Obstacle => Some(theme.obstacle),
+                    Tile::Portal => Some(theme.portal),
+                    Tile::Hazard => Some(theme.hazard),
+                    Tile::SnakePart(SnakePart::Head(_), _) => Some(theme.snake(owners[y][x]).0),
+                    Tile::SnakePart(_, _) => Some(theme.snake(owners[y][x]).1),
+                };
+                if let Some(color) = color {
+                    self.out.queue(SetForegroundColor(rgb(color)))?;
+                }
+                // only the reverse video is turned off after a bulge,
+                // the colours stay for the rest of the row
+                if let Tile::SnakePart(_, true) = tile {
+                    self.out
+                        .queue(SetAttribute(Attribute::Reverse))?
+                        .queue(Print(get_char(tile)))?
+                        .queue(SetAttribute(Attribute::NoReverse))?;
+                } else {
+                    self.out.queue(Print(get_char(tile)))?;
+                }
+            }
+            self.out
+                .queue(ResetColor)?
+                .queue(Print(format!("{side}\n\r")))?;
+        }
+
+        // bottom line
+        self.out
+            .queue(Print(format!(
+                "{bottom}\n\r\
+         {additional_text}\n\r\
+         Press {} to exit...",
+                self.keys
+                    .keys(Action::Quit)
+                    .first()
+                    .map_or("-".to_owned(), Key::label)
+            )))?
+            .flush()?;
+
+        Ok(())
+    }
+
+    // the keys of every action, until any key is pressed
+    fn show_controls(&mut self) -> Result<()> {
+        self.out
+            .queue(terminal::Clear(ClearType::All))?
+            .queue(cursor::MoveTo(0, 0))?
+            .queue(Print("Controls\n\r\n\r"))?;
+        for action in Action::ALL {
+            self.out.queue(Print(format!(
+                "{:<24}{}\n\r",
+                action.description(),
+                self.keys.describe(action)
+            )))?;
+        }
+        self.out
+            .queue(Print(
+                "\n\rA single player can also steer with the keys of the second player.\n\r\
+                 The keys can be changed in the config file.\n\r\n\r\
+                 Press any key to go back to the game...",
+            ))?
+            .flush()?;
+        while !matches!(event::read()?, Event::Key(_)) {}
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "terminal")]
+fn rgb([r, g, b]: theme::Color) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+// snake is drawn using Box Drawing Unicode char block
+#[cfg(feature = "terminal")]
+fn get_char(tile: &Tile) -> char {
+    match *tile {
+        Tile::Empty => ' ',
+        Tile::Food(FoodType::Blob) => '*',
+        Tile::Food(FoodType::PowerUp(power_up)) => match power_up {
+            PowerUp::Ghost => 'G',
+            PowerUp::Magnet => 'M',
+            PowerUp::Shrink => 'S',
+            PowerUp::SlowMotion => 'W',
+        },
+        Tile::Obstacle => '@',
+        Tile::Portal => 'O',
+        Tile::Hazard => 'X',
+        Tile::SnakePart(snake_part, _) => match snake_part {
+            SnakePart::Head(direction) | SnakePart::Tail(direction) => match direction {
+                Direction::Right => '>',
+                Direction::Left => '<',
+                Direction::Up => '⌃',
+                Direction::Down => '⌄',
+            },
+            SnakePart::Body(direction) => match direction {
+                BodyPartDirection::Up | BodyPartDirection::Down => '┃',
+                BodyPartDirection::Left | BodyPartDirection::Right => '━',
+                BodyPartDirection::TopLeftCornerRight | BodyPartDirection::TopLeftCornerDown => '┏',
+                BodyPartDirection::TopRightCornerLeft | BodyPartDirection::TopRightCornerDown => {
+                    '┓'
+                }
+                BodyPartDirection::BottomLeftCornerRight
+                | BodyPartDirection::BottomLeftCornerUp => '┗',
+                BodyPartDirection::BottomRightCornerLeft
+                | BodyPartDirection::BottomRightCornerUp => '┛',
+            },
+        },
+    }
+}
+
+// swallowed food travels down the snake as a bulge, drawn as its
+// glyph in reverse video until it reaches the tail
+#[cfg(feature = "terminal")]
+fn styled_char(tile: &Tile) -> StyledContent<char> {
+    match *tile {
+        Tile::SnakePart(_, true) => get_char(tile).reverse(),
+        _ => get_char(tile).stylize(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::ScriptedController;
+    use std::io::{self, Sink};
+
+    fn game(width: usize, height: usize) -> SnakeGame<GameRng, Sink> {
+        SnakeGame::with_seed(width, height, io::sink(), 1)
+    }
+
+    #[test]
+    fn board_sizes_are_checked() {
+        assert!(check_board_size(MIN_BOARD_SIZE, MAX_BOARD_SIZE).is_ok());
+        assert!(check_board_size(MIN_BOARD_SIZE - 1, BOARD_HEIGHT).is_err());
+        assert!(check_board_size(BOARD_WIDTH, 0).is_err());
+        assert!(check_board_size(MAX_BOARD_SIZE + 1, BOARD_HEIGHT).is_err());
+    }
+
+    #[test]
+    fn spawned_snakes_move_on_the_smallest_board() {
+        let mut game = game(MIN_BOARD_SIZE, MIN_BOARD_SIZE);
+        let snake = Snake::spawn(0, 1, MIN_BOARD_SIZE, MIN_BOARD_SIZE);
+        assert_eq!(snake.len(), 3);
+        game.add_snake(snake, Box::new(ScriptedController::new([])));
+        game.spawn_food();
+        for _ in 0..3 {
+            game.step();
+        }
+
+        assert_eq!(game.tick(), 3);
+    }
+
+    // a snake of three tiles whose head is at the position, moving in the direction
+    fn snake_heading(direction: Direction, head: (usize, usize), width: usize) -> Snake {
+        let behind = direction.opposite();
+        let body = behind.apply(head, width, width);
+        let cells = [head, body, behind.apply(body, width, width)];
+
+        Snake::from_cells(&cells, width, width)
+    }
+
+    // where the head of the only snake is after one step
+    fn step_once(mut game: SnakeGame<GameRng, Sink>, snake: Snake) -> Option<(usize, usize)> {
+        game.add_snake(snake, Box::new(ScriptedController::new([])));
+        game.step();
+        let snake = &game.snakes()[0];
+
+        snake.is_alive().then(|| snake.head().position())
+    }
+
+    #[test]
+    fn snakes_come_out_of_portals_in_every_direction() {
+        // into each end of the portal from every side
+        for (direction, head, exit) in [
+            (Direction::Up, (5, 3), (2, 6)),
+            (Direction::Up, (2, 8), (5, 1)),
+            (Direction::Right, (4, 2), (3, 7)),
+            (Direction::Right, (1, 7), (6, 2)),
+            (Direction::Down, (5, 1), (2, 8)),
+            (Direction::Down, (2, 6), (5, 3)),
+            (Direction::Left, (6, 2), (1, 7)),
+            (Direction::Left, (3, 7), (4, 2)),
+        ] {
+            let mut game = game(10, 10);
+            game.add_portals(&[[(5, 2), (2, 7)]]);
+            let snake = snake_heading(direction, head, 10);
+
+            assert_eq!(
+                step_once(game, snake),
+                Some(exit),
+                "{direction:?} from {head:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn portals_lead_out_of_the_board_only_without_walls() {
+        for (boundary, head) in [(Boundary::Wrap, Some((9, 2))), (Boundary::Walls, None)] {
+            let mut game = game(10, 10);
+            game.set_boundary(boundary);
+            game.add_portals(&[[(5, 5), (0, 2)]]);
+            let snake = snake_heading(Direction::Left, (6, 5), 10);
+
+            assert_eq!(step_once(game, snake), head, "{}", boundary.name());
+        }
+    }
+
+    #[test]
+    fn portals_next_to_an_obstacle_lead_into_it() {
+        let mut game = game(10, 10);
+        game.add_portals(&[[(5, 5), (2, 2)]]);
+        game.add_obstacles(&[(1, 2)]);
+        let snake = snake_heading(Direction::Left, (6, 5), 10);
+
+        assert_eq!(step_once(game, snake), None);
+    }
+
+    #[test]
+    fn food_never_spawns_on_portals() {
+        // all of the board but the snake and three tiles is portals
+        let snake = [(2, 0), (1, 0), (0, 0)];
+        let empty = [(3, 0), (0, 1), (1, 1)];
+        let tiles: Vec<_> = (0..4)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|tile| !snake.contains(tile) && !empty.contains(tile))
+            .collect();
+        let portals: Vec<_> = tiles.chunks(2).map(|pair| [pair[0], pair[1]]).collect();
+
+        for seed in 0..20 {
+            let mut game = SnakeGame::<GameRng, _>::with_seed(4, 4, io::sink(), seed);
+            game.add_portals(&portals);
+            game.add_snake(
+                Snake::from_cells(&snake, 4, 4),
+                Box::new(ScriptedController::new([])),
+            );
+            game.spawn_food();
+
+            let board = game.board();
+            assert!(tiles.iter().all(|&(x, y)| board[y][x] == Tile::Portal));
+            assert!(empty.iter().all(|&(x, y)| board[y][x].has_food()));
+        }
+    }
+
+    // a snake lying over itself, the tile in front of the head
+    // is also the fifth one of its body
+    fn crossed_ghost(steps: u64) -> Snake {
+        let cells = [
+            (4, 2),
+            (3, 2),
+            (2, 2),
+            (2, 3),
+            (3, 3),
+            (3, 2),
+            (3, 1),
+            (3, 0),
+        ];
+        let mut snake = Snake::from_cells(&cells, 10, 10);
+        snake.effects.push((PowerUp::Ghost, steps));
+
+        snake
+    }
+
+    #[test]
+    fn ghosts_lying_over_themselves_die_when_it_wears_off() {
+        for (steps, alive) in [(2, true), (1, false)] {
+            let mut game = game(10, 10);
+            game.add_snake(crossed_ghost(steps), Box::new(ScriptedController::new([])));
+            game.step();
+
+            assert_eq!(game.snakes()[0].is_alive(), alive, "{steps} steps left");
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use crate::{
+    check_board_size,
+    config::{Action, KeyBindings},
+    controller::KeyboardController,
+    net::{self, RemoteController},
+    Direction, GameRng, GameState, Snake, SnakeGame, Snapshot, RULES_VERSION,
+};
+use crossterm::{
+    event::{self, Event, KeyEvent},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+// steps between comparing the games of both peers
+const HASH_INTERVAL: u64 = 20;
+// a peer not sending its move in this time is gone
+const PEER_TIMEOUT: Duration = Duration::from_secs(5);
+
+// sent between the peers, one JSON object per line
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum PeerMessage {
+    // from the connecting peer; games only play out
+    // the same way with the same rules
+    Hello {
+        version: u32,
+    },
+    // the answer of the listening peer, everything
+    // needed to set up the same game on both sides
+    Start {
+        seed: u64,
+        width: usize,
+        height: usize,
+        step_length: u64,
+    },
+    Refused {
+        reason: String,
+    },
+    // the move of the peer's snake for the step after `tick`, None
+    // to keep going, and every HASH_INTERVAL steps a hash of its game
+    Input {
+        tick: u64,
+        direction: Option<Direction>,
+        hash: Option<u64>,
+    },
+    // the game of the listening peer, which the connecting
+    // peer takes over when the hashes differ
+    Resync {
+        game: Box<Snapshot<GameRng>>,
+    },
+}
+
+// one of the two sides of a game played by only exchanging moves: both
+// run the same seeded game and apply the moves of both snakes every step,
+// so the game plays out the same way on both sides
+pub struct Peer<W: Write> {
+    game: SnakeGame<GameRng, W>,
+    stream: TcpStream,
+    messages: Receiver<PeerMessage>,
+    // the own snake, the listening peer has the first one
+    you: usize,
+    // inputs[i] steers snakes[i]; the one of the other peer's snake
+    // is dropped when it is gone, which removes the snake
+    inputs: Vec<Option<Sender<Direction>>>,
+    keyboard: KeyboardController,
+    // keys pressed, one is sent every step
+    moves: VecDeque<Direction>,
+    connected: bool,
+    desyncs: u32,
+}
+
+impl<W: Write> Peer<W> {
+    // waits on the listener for the other player and sets up a new game
+    pub fn listen(
+        listener: &TcpListener,
+        (width, height): (usize, usize),
+        step_length: Duration,
+        out: W,
+    ) -> io::Result<Self> {
+        let (mut stream, _) = listener.accept()?;
+        stream.set_nodelay(true)?;
+        let messages = net::receive(stream.try_clone()?);
+        match messages.recv_timeout(PEER_TIMEOUT) {
+            Ok(PeerMessage::Hello { version }) if version == RULES_VERSION => {}
+            Ok(PeerMessage::Hello { version }) => {
+                let reason = format!(
+                    "the other player has rules version {RULES_VERSION}, this game has version {version}"
+                );
+                net::send(&mut stream, &PeerMessage::Refused { reason }).ok();
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("the other player has rules version {version}"),
+                ));
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the other player did not say hello",
+                ))
+            }
+        }
+
+        let seed = rand::random();
+        let start = PeerMessage::Start {
+            seed,
+            width,
+            height,
+            step_length: step_length.as_millis() as u64,
+        };
+        net::send(&mut stream, &start)?;
+
+        Ok(Self::new(
+            stream,
+            messages,
+            0,
+            seed,
+            (width, height),
+            step_length,
+            out,
+        ))
+    }
+
+    // joins a player waiting with `listen`, who decides about the game
+    pub fn connect(address: &str, out: W) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        let messages = net::receive(stream.try_clone()?);
+        let hello = PeerMessage::Hello {
+            version: RULES_VERSION,
+        };
+        net::send(&mut stream, &hello)?;
+
+        match messages.recv_timeout(PEER_TIMEOUT) {
+            Ok(PeerMessage::Start {
+                seed,
+                width,
+                height,
+                step_length,
+            }) => {
+                check_board_size(width, height)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                let step_length = Duration::from_millis(step_length);
+                Ok(Self::new(
+                    stream,
+                    messages,
+                    1,
+                    seed,
+                    (width, height),
+                    step_length,
+                    out,
+                ))
+            }
+            Ok(PeerMessage::Refused { reason }) => {
+                Err(io::Error::new(io::ErrorKind::ConnectionRefused, reason))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the other player did not start a game",
+            )),
+        }
+    }
+
+    fn new(
+        stream: TcpStream,
+        messages: Receiver<PeerMessage>,
+        you: usize,
+        seed: u64,
+        (width, height): (usize, usize),
+        step_length: Duration,
+        out: W,
+    ) -> Self {
+        let mut game = SnakeGame::with_seed(width, height, out, seed);
+        game.set_step_length(step_length);
+        let mut inputs = Vec::new();
+        for i in 0..2 {
+            let (sender, receiver) = mpsc::channel();
+            game.add_snake(
+                Snake::spawn(i, 2, width, height),
+                Box::new(RemoteController::new(receiver)),
+            );
+            inputs.push(Some(sender));
+        }
+        game.spawn_food();
+
+        Self {
+            game,
+            stream,
+            messages,
+            you,
+            inputs,
+            keyboard: KeyboardController::default(),
+            moves: VecDeque::new(),
+            connected: true,
+            desyncs: 0,
+        }
+    }
+
+    pub fn game(&self) -> &SnakeGame<GameRng, W> {
+        &self.game
+    }
+
+    pub fn set_key_bindings(&mut self, keys: KeyBindings) {
+        self.keyboard = keys.keyboard(0, 1);
+        self.game.set_key_bindings(keys);
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.game.set_up_screen()?;
+        let result = self.play();
+        self.game.tear_down_screen()?;
+
+        result
+    }
+
+    fn play(&mut self) -> Result<()> {
+        loop {
+            self.draw()?;
+            if self.game.state != GameState::Running {
+                while !matches!(event::read()?, Event::Key(_)) {}
+                return Ok(());
+            }
+
+            let next_step = Instant::now() + self.game.step_length;
+            while event::poll(next_step.saturating_duration_since(Instant::now()))? {
+                if let Event::Key(KeyEvent { code, .. }) = event::read()? {
+                    if self.game.keys.action(code) == Some(Action::Quit) {
+                        return Ok(());
+                    }
+                    if let Some(direction) = self.keyboard.direction(code) {
+                        self.moves.push_back(direction);
+                    }
+                }
+            }
+
+            self.exchange();
+            self.game.step();
+        }
+    }
+
+    // sends the own move for the next step, waits for the one of the
+    // other peer and hands both to the snakes; every HASH_INTERVAL steps
+    // the games are compared as well
+    fn exchange(&mut self) {
+        let tick = self.game.tick;
+        let direction = self.moves.pop_front();
+        let hash = tick.is_multiple_of(HASH_INTERVAL).then(|| hash(&self.game));
+        let mut peer_direction = None;
+
+        if self.connected {
+            let input = PeerMessage::Input {
+                tick,
+                direction,
+                hash,
+            };
+            match net::send(&mut self.stream, &input)
+                .ok()
+                .and_then(|()| self.receive_input(tick))
+            {
+                Some((direction, peer_hash)) => {
+                    peer_direction = direction;
+                    if hash.is_some() && peer_hash.is_some() && hash != peer_hash {
+                        self.resync();
+                    }
+                }
+                None => self.disconnect(),
+            }
+        }
+
+        for (snake, direction) in [(self.you, direction), (1 - self.you, peer_direction)] {
+            if let (Some(input), Some(direction)) = (&self.inputs[snake], direction) {
+                input.send(direction).ok();
+            }
+        }
+    }
+
+    // the move and hash of the other peer for the step after the tick,
+    // None if it is gone
+    fn receive_input(&mut self, tick: u64) -> Option<(Option<Direction>, Option<u64>)> {
+        loop {
+            match self.messages.recv_timeout(PEER_TIMEOUT).ok()? {
+                PeerMessage::Input {
+                    tick: peer_tick,
+                    direction,
+                    hash,
+                } if peer_tick == tick => return Some((direction, hash)),
+                _ => {}
+            }
+        }
+    }
+
+    // the games went out of sync, the connecting peer
+    // continues with the game of the listening one
+    fn resync(&mut self) {
+        self.desyncs += 1;
+        if self.you == 0 {
+            let resync = PeerMessage::Resync {
+                game: Box::new(self.game.snapshot()),
+            };
+            if net::send(&mut self.stream, &resync).is_err() {
+                self.disconnect();
+            }
+            return;
+        }
+
+        loop {
+            match self.messages.recv_timeout(PEER_TIMEOUT) {
+                Ok(PeerMessage::Resync { game }) => {
+                    self.game.restore(&game);
+                    return;
+                }
+                Ok(_) => {}
+                Err(_) => {
+                    self.disconnect();
+                    return;
+                }
+            }
+        }
+    }
+
+    // the game goes on without the other peer's snake
+    fn disconnect(&mut self) {
+        self.connected = false;
+        self.inputs[1 - self.you] = None;
+        self.stream.shutdown(Shutdown::Both).ok();
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let peer = if self.connected {
+            "connected"
+        } else {
+            "the other player left"
+        };
+        let state = match self.game.state {
+            GameState::Running => "",
+            GameState::Won => "\n\rThe board is full! Press any key...",
+            GameState::Lost => "\n\rGame over! Press any key...",
+        };
+        let text = format!(
+            "You are snake {}, steer with {}\n\r\
+             tick {}, {peer}, {} desyncs{}{state}",
+            self.you + 1,
+            self.game.keys.steering(),
+            self.game.tick,
+            self.desyncs,
+            self.game.scoreboard()
+        );
+
+        self.game.add_snake_to_board();
+        let result = self.game.draw(&text);
+        self.game.remove_snake_from_board();
+
+        result
+    }
+}
+
+// FNV-1a of everything the following steps depend on; of the serialized
+// game rather than a derived `Hash`, which may differ between platforms
+fn hash<W: Write>(game: &SnakeGame<GameRng, W>) -> u64 {
+    let state = serde_json::to_vec(&(&game.board, &game.snakes, &game.rng, game.tick)).unwrap();
+    state.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Sink, thread};
+
+    const TICKS: u64 = 60;
+
+    // steps the game like `play`, with turns at the ticks instead of keys;
+    // the own snake's score is changed after the tick `tamper`, which only
+    // the hashes notice; returns the hash after every step and the desyncs
+    fn play(
+        mut peer: Peer<Sink>,
+        turns: &[(u64, Direction)],
+        tamper: Option<u64>,
+    ) -> (Vec<u64>, u32) {
+        let mut hashes = Vec::new();
+        while peer.game.state == GameState::Running && peer.game.tick < TICKS {
+            let tick = peer.game.tick;
+            if let Some(&(_, direction)) = turns.iter().find(|&&(at, _)| at == tick) {
+                peer.moves.push_back(direction);
+            }
+            peer.exchange();
+            peer.game.step();
+            if tamper == Some(tick) {
+                peer.game.snakes[peer.you].score += 10;
+            }
+            hashes.push(hash(&peer.game));
+        }
+
+        (hashes, peer.desyncs)
+    }
+
+    // plays a game between two peers on this machine, only the connecting
+    // one tampers with its game
+    fn play_both(tamper: Option<u64>) -> [(Vec<u64>, u32); 2] {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let listening = thread::spawn(move || {
+            let peer = Peer::listen(&listener, (20, 10), Duration::ZERO, io::sink()).unwrap();
+            play(peer, &[(3, Direction::Up), (5, Direction::Right)], None)
+        });
+        let connecting = thread::spawn(move || {
+            let peer = Peer::connect(&address, io::sink()).unwrap();
+            play(peer, &[(4, Direction::Down), (6, Direction::Right)], tamper)
+        });
+
+        [listening.join().unwrap(), connecting.join().unwrap()]
+    }
+
+    #[test]
+    fn peers_with_the_same_inputs_stay_in_sync() {
+        let [(listening, listening_desyncs), (connecting, connecting_desyncs)] = play_both(None);
+        assert_eq!(listening.len(), TICKS as usize);
+        assert_eq!(listening, connecting);
+        assert_eq!((listening_desyncs, connecting_desyncs), (0, 0));
+    }
+
+    #[test]
+    fn a_desync_is_repaired_with_the_listening_peers_game() {
+        let tamper = 5;
+        let [(listening, listening_desyncs), (connecting, connecting_desyncs)] =
+            play_both(Some(tamper));
+        assert_eq!(listening.len(), connecting.len());
+        for (tick, (a, b)) in (1..).zip(listening.iter().zip(&connecting)) {
+            // the games are compared before the step after the tick
+            if tick <= tamper || tick > HASH_INTERVAL {
+                assert_eq!(a, b, "tick {tick}");
+            } else {
+                assert_ne!(a, b, "tick {tick}");
+            }
+        }
+        assert_eq!((listening_desyncs, connecting_desyncs), (1, 1));
+    }
+}
@@ -1,966 +1,843 @@
-use crossterm::{
-    cursor,
-    event::{self, Event, KeyCode, KeyEvent},
-    style::Print,
-    terminal::{self, ClearType},
-    QueueableCommand, Result,
+use clap::{builder::RangedU64ValueParser, Parser, Subcommand};
+use crossterm::Result;
+use snake::{
+    campaign::{Level, Progress, LEVELS},
+    cast::{self, CastRecorder},
+    config::{Config, KeyBindings, Settings},
+    controller::{Controller, ControllerKind},
+    daily::{Challenge, DailyResult, DailyResults},
+    lockstep::Peer,
+    menu::{Choice, Menu},
+    net, render,
+    replay::Replay,
+    save::{Origin, SavedGame},
+    scores::{self, HighScore, HighScores},
+    solver, svg,
+    theme::Theme,
+    tournament,
+    viewer::Viewer,
+    Boundary, Exit, GameRng, GameState, Rules, Snake, SnakeGame, BOARD_HEIGHT, BOARD_WIDTH,
+    MAX_BOARD_SIZE, MIN_BOARD_SIZE,
 };
-use rand::{prelude::SmallRng, Rng, SeedableRng};
 use std::{
-    fmt::Write,
-    io::{stdout, Stdout, Write as IOWrite},
-    ops::SubAssign,
+    io::{stdout, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
     time::Duration,
 };
-use std::{ops::AddAssign, time::SystemTime};
-
-const BOARD_WIDTH: usize = 50;
-const BOARD_HEIGHT: usize = 20;
-const STEP_LENGTH: u64 = 300;
-const GAME_STEP_LENGTH: Duration = Duration::from_millis(STEP_LENGTH);
-const MAX_FOOD_ON_BOARD: usize = 20;
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum BodyPartDirection {
-    Up,
-    Down,
-    Left,
-    Right,
-    TopLeftCornerRight,
-    TopLeftCornerDown,
-    TopRightCornerLeft,
-    TopRightCornerDown,
-    BottomLeftCornerRight,
-    BottomLeftCornerUp,
-    BottomRightCornerLeft,
-    BottomRightCornerUp,
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Tile {
-    Empty,
-    Food(FoodType),
-    //Obstacle,
-    SnakePart(SnakePart, bool),
-}
-
-impl Tile {
-    fn is_empty(&self) -> bool {
-        *self == Tile::Empty
-    }
-
-    fn has_food(&self) -> bool {
-        matches!(*self, Tile::Food(_))
-    }
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum FoodType {
-    Blob,
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum SnakePart {
-    Head(Direction),
-    Body(BodyPartDirection),
-    Tail(Direction),
-}
-
-type Board = Vec<Vec<Tile>>;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct SnakeTile {
-    x: Wrap,
-    y: Wrap,
-    snake_tile_type: SnakePart,
-    eating: bool,
+#[derive(Parser)]
+#[command(version, about = "A simple terminal snake game")]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Let the Hamiltonian cycle solver play until the board is full
+    #[arg(long, conflicts_with = "snake")]
+    autopilot: bool,
+    /// Add a snake driven by `keyboard`, `bot`, `script:<moves>`, where
+    /// moves are w, a, s, d or . (keep going) for each step, or
+    /// `external:<command>`, a bot process talking JSON over stdin/stdout
+    #[arg(long)]
+    snake: Vec<ControllerKind>,
+    /// Time an external bot has to reply each step in milliseconds
+    #[arg(long, default_value_t = 100, global = true)]
+    move_time_limit: u64,
+    /// Length of one game step in milliseconds, the one
+    /// from the settings if not given
+    #[arg(long, global = true)]
+    step_length: Option<u64>,
+    /// Directory the replay of every finished game is written to
+    #[arg(long, default_value = "replays", global = true)]
+    replay_dir: PathBuf,
+    /// File the high scores of single player games are kept in
+    #[arg(long, default_value = "snake-scores.json")]
+    scores_file: PathBuf,
+    /// File the results of the daily challenges are kept in
+    #[arg(long, default_value = "snake-daily.json")]
+    daily_file: PathBuf,
+    /// File the completed campaign levels are kept in
+    #[arg(long, default_value = "snake-campaign.json")]
+    campaign_file: PathBuf,
+    /// File the game is written to when saving it with x
+    #[arg(long, default_value = "snake-save.json")]
+    save_file: PathBuf,
+    /// Also record the game to this asciinema (.cast) file
+    #[arg(long)]
+    cast: Option<PathBuf>,
+    /// Let others watch the game over the network, connecting
+    /// with `watch` to this port
+    #[arg(long)]
+    spectator_port: Option<u16>,
+    /// Settings like the keyboard layout and key bindings, see the README
+    #[arg(long, default_value = "snake-config.json", global = true)]
+    config: PathBuf,
+    /// Continue a saved game
+    #[arg(long, conflicts_with_all = ["autopilot", "snake"])]
+    resume: Option<PathBuf>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-struct Wrap {
-    modulus: usize,
-    number: usize,
+#[derive(Subcommand)]
+enum Command {
+    /// Watch a recorded game
+    Replay {
+        /// Replay file written at the end of a game
+        path: PathBuf,
+    },
+    /// Turn a replay into an asciinema recording
+    Cast {
+        /// Replay file written at the end of a game
+        replay: PathBuf,
+        /// The .cast file to write
+        output: PathBuf,
+    },
+    /// Render a replay as an animated GIF, or as numbered PNG frames
+    Image {
+        /// Replay file written at the end of a game
+        replay: PathBuf,
+        /// The GIF file, or the directory for the PNG frames with --png
+        output: PathBuf,
+        /// Write one PNG file per step instead of a GIF
+        #[arg(long)]
+        png: bool,
+        /// Size of a tile in pixels
+        #[arg(long, default_value_t = 8, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        tile_size: usize,
+        /// Colours to use: classic, light or mono
+        #[arg(long, default_value = "classic")]
+        theme: String,
+    },
+    /// Draw the board of a replay as an SVG image
+    Svg {
+        /// Replay file written at the end of a game
+        replay: PathBuf,
+        /// The .svg file to write
+        output: PathBuf,
+        /// Step to draw the board at, the end of the game if not given
+        #[arg(long)]
+        tick: Option<u64>,
+        /// Colours to use: classic, light or mono
+        #[arg(long, default_value = "classic")]
+        theme: String,
+    },
+    /// Host a game for players joining over the network
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = net::DEFAULT_PORT)]
+        port: u16,
+        /// Number of players, the game starts once all of them joined
+        #[arg(long, default_value_t = 2)]
+        players: usize,
+        #[arg(long, default_value_t = BOARD_WIDTH, value_parser = board_size)]
+        width: usize,
+        #[arg(long, default_value_t = BOARD_HEIGHT, value_parser = board_size)]
+        height: usize,
+    },
+    /// Play in a game hosted with `serve`
+    Join {
+        /// Address of the server, `host` or `host:port`
+        address: String,
+    },
+    /// Play against one other player, both sides run the same game
+    /// and only exchange their moves
+    Lockstep {
+        /// Wait for the other player on this port
+        #[arg(long, required_unless_present = "connect", conflicts_with = "connect")]
+        listen: Option<u16>,
+        /// Play with a player waiting with --listen, `host` or `host:port`
+        #[arg(long)]
+        connect: Option<String>,
+        #[arg(long, default_value_t = BOARD_WIDTH, value_parser = board_size)]
+        width: usize,
+        #[arg(long, default_value_t = BOARD_HEIGHT, value_parser = board_size)]
+        height: usize,
+    },
+    /// Watch a game hosted with `serve` or `--spectator-port`
+    Watch {
+        /// Address of the server, `host` or `host:port`
+        address: String,
+    },
+    /// Play many seeded games between bots without drawing them
+    /// and print a leaderboard
+    Tournament {
+        /// Add a bot to the tournament, see --snake for the controllers
+        /// (except keyboard)
+        #[arg(long = "bot", required = true)]
+        bots: Vec<ControllerKind>,
+        /// Number of games to play
+        #[arg(long, default_value_t = 100)]
+        games: u64,
+        /// Seed of the first game, each following game uses the next one
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Games still running after this many steps are stopped
+        #[arg(long, default_value_t = 10_000)]
+        max_ticks: u64,
+        #[arg(long, default_value_t = BOARD_WIDTH, value_parser = board_size)]
+        width: usize,
+        #[arg(long, default_value_t = BOARD_HEIGHT, value_parser = board_size)]
+        height: usize,
+        /// Also write the results as JSON to this file, `-` prints
+        /// them instead of the leaderboard
+        #[arg(long)]
+        summary: Option<PathBuf>,
+    },
 }
 
-impl Wrap {
-    fn new(number: usize, modulus: usize) -> Self {
-        Self {
-            modulus,
-            number: number % modulus,
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let move_time_limit = Duration::from_millis(args.move_time_limit);
+    let config = match Config::load(&args.config) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Could not load the config {}: {err}", args.config.display());
+            return Ok(());
         }
-    }
+    };
+    let keys = config.key_bindings();
+    let step_length =
+        Duration::from_millis(args.step_length.unwrap_or(config.settings.step_length));
+    if let Some(Command::Replay { path }) = &args.command {
+        let replay = match Replay::load(path) {
+            Ok(replay) => replay,
+            Err(err) => {
+                eprintln!("Could not load the replay {}: {err}", path.display());
+                return Ok(());
+            }
+        };
 
-    // increment the number, wrap if needed
-    fn inc(&mut self) {
-        self.number = if self.number == self.modulus - 1 {
-            0
-        } else {
-            self.number + 1
-        }
+        return Viewer::new(replay, stdout(), keys).run();
     }
-
-    // decrement the number, wrap if needed
-    fn dec(&mut self) {
-        self.number = if self.number == 0 {
-            self.modulus - 1
-        } else {
-            self.number - 1
+    if let Some(Command::Cast { replay, output }) = &args.command {
+        let result = Replay::load(replay).and_then(|replay| cast::export(&replay, output));
+        match result {
+            Ok(()) => println!("Recording written to {}", output.display()),
+            Err(err) => eprintln!("Could not convert the replay {}: {err}", replay.display()),
         }
-    }
-}
 
-impl AddAssign<usize> for Wrap {
-    fn add_assign(&mut self, rhs: usize) {
-        self.number = if self.number + rhs == self.modulus {
-            0
-        } else {
-            (self.number + rhs) % self.modulus
-        }
+        return Ok(());
     }
-}
-
-impl SubAssign<usize> for Wrap {
-    fn sub_assign(&mut self, rhs: usize) {
-        self.number = if self.number as isize - rhs as isize == -1 {
-            self.modulus - 1
-        } else {
-            self.number - rhs
+    if let Some(Command::Image {
+        replay,
+        output,
+        png,
+        tile_size,
+        theme,
+    }) = &args.command
+    {
+        let Some(theme) = Theme::by_name(theme) else {
+            eprintln!("Unknown theme '{theme}'");
+            return Ok(());
+        };
+        let result = Replay::load(replay).and_then(|replay| {
+            if *png {
+                render::export_png(&replay, output, theme, *tile_size).map(|_| ())
+            } else {
+                render::export_gif(&replay, output, theme, *tile_size)
+            }
+        });
+        match result {
+            Ok(()) => println!("Images written to {}", output.display()),
+            Err(err) => eprintln!("Could not render the replay {}: {err}", replay.display()),
         }
-    }
-}
 
-impl From<Wrap> for usize {
-    fn from(w: Wrap) -> Self {
-        w.number
+        return Ok(());
     }
-}
-
-fn main() -> Result<()> {
-    let width = BOARD_WIDTH;
-    let height = BOARD_HEIGHT;
-
-    let mut game: SnakeGame<SmallRng, Stdout> = SnakeGame::new(width, height, stdout());
-    game.set_up_screen()?;
-    game.play()?;
-    game.tear_down_screen()?;
-
-    Ok(())
-}
-
-// TODO: move game structs and logic to module to make internals private
-#[derive(Debug)]
-struct Snake {
-    body: Vec<SnakeTile>,
-}
-
-impl Snake {
-    pub fn new() -> Self {
-        Snake {
-            body: Vec::with_capacity(100),
-        }
-    }
-
-    pub fn sample_snake(board_width: usize, board_height: usize) -> Self {
-        Snake {
-            body: vec![
-                SnakeTile {
-                    x: Wrap::new(10, board_width),
-                    y: Wrap::new(10, board_height),
-                    snake_tile_type: SnakePart::Head(Direction::Left),
-                    eating: false,
-                },
-                SnakeTile {
-                    x: Wrap::new(11, board_width),
-                    y: Wrap::new(10, board_height),
-                    snake_tile_type: SnakePart::Body(BodyPartDirection::TopRightCornerLeft),
-                    eating: false,
-                },
-                SnakeTile {
-                    x: Wrap::new(11, board_width),
-                    y: Wrap::new(11, board_height),
-                    snake_tile_type: SnakePart::Body(BodyPartDirection::Up),
-                    eating: false,
-                },
-                SnakeTile {
-                    x: Wrap::new(11, board_width),
-                    y: Wrap::new(12, board_height),
-                    snake_tile_type: SnakePart::Body(BodyPartDirection::Up),
-                    eating: false,
-                },
-                SnakeTile {
-                    x: Wrap::new(11, board_width),
-                    y: Wrap::new(13, board_height),
-                    snake_tile_type: SnakePart::Body(BodyPartDirection::BottomLeftCornerUp),
-                    eating: false,
-                },
-                SnakeTile {
-                    x: Wrap::new(12, board_width),
-                    y: Wrap::new(13, board_height),
-                    snake_tile_type: SnakePart::Tail(Direction::Left),
-                    eating: false,
-                },
-            ],
+    if let Some(Command::Svg {
+        replay,
+        output,
+        tick,
+        theme,
+    }) = &args.command
+    {
+        let Some(theme) = Theme::by_name(theme) else {
+            eprintln!("Unknown theme '{theme}'");
+            return Ok(());
+        };
+        let result =
+            Replay::load(replay).and_then(|replay| svg::export(&replay, output, theme, *tick));
+        match result {
+            Ok(()) => println!("Board written to {}", output.display()),
+            Err(err) => eprintln!("Could not draw the replay {}: {err}", replay.display()),
         }
-    }
 
-    pub fn len(&self) -> usize {
-        self.body.len()
+        return Ok(());
     }
-
-    pub fn head(&self) -> &SnakeTile {
-        &self.body[0]
-    }
-
-    pub fn last(&self) -> &SnakeTile {
-        self.body.last().unwrap()
-    }
-
-    pub fn head_mut(&mut self) -> &mut SnakeTile {
-        &mut self.body[0]
-    }
-
-    pub fn whole_snake(&self) -> &Vec<SnakeTile> {
-        &self.body
-    }
-}
-
-struct SnakeGame<R: SeedableRng + Rng, W: IOWrite> {
-    out: W,
-    board: Board,
-    board_width: usize,
-    board_height: usize,
-    snake: Snake,
-    score: u32,
-    rng: R,
-}
-
-impl<R: SeedableRng + Rng, W: IOWrite> SnakeGame<R, W> {
-    pub fn new(board_width: usize, board_height: usize, out: W) -> Self {
-        SnakeGame {
-            out,
-            board: vec![vec![Tile::Empty; board_width]; board_height],
-            board_width,
-            board_height,
-            snake: Snake::sample_snake(board_width, board_height),
-            score: 0,
-            rng: R::from_entropy(),
+    if let Some(Command::Serve {
+        port,
+        players,
+        width,
+        height,
+    }) = &args.command
+    {
+        let listener = TcpListener::bind(("0.0.0.0", *port))?;
+        println!("Waiting for {players} players on port {port}");
+        let game = net::serve(listener, *players, (*width, *height), step_length, |line| {
+            println!("{line}")
+        })?;
+        match game.replay().save(&args.replay_dir) {
+            Ok(path) => println!("Replay saved to {}", path.display()),
+            Err(err) => eprintln!("Could not save the replay: {err}"),
         }
-    }
 
-    pub fn set_up_screen(&mut self) -> Result<()> {
-        self.out
-            .queue(cursor::Hide)?
-            .queue(terminal::EnterAlternateScreen)?
-            .queue(cursor::MoveTo(0, 0))?
-            .flush()?;
-        terminal::enable_raw_mode()?;
-
-        Ok(())
+        return Ok(());
     }
+    if let Some(Command::Join { address } | Command::Watch { address }) = &args.command {
+        let watch = matches!(args.command, Some(Command::Watch { .. }));
+        let address = with_port(address);
+        if let Err(err) = net::join(&address, stdout(), watch, keys) {
+            eprintln!("Could not connect to {address}: {err}");
+        }
 
-    pub fn tear_down_screen(&mut self) -> Result<()> {
-        terminal::disable_raw_mode()?;
-        self.out
-            .queue(cursor::Show)?
-            .queue(terminal::LeaveAlternateScreen)?
-            .flush()?;
-
-        Ok(())
+        return Ok(());
     }
-
-    pub fn play(&mut self) -> Result<()> {
-        let mut timer;
-        let mut step_time = Duration::ZERO;
-
-        loop {
-            timer = SystemTime::now();
-            for _ in 0..5 {
-                self.add_food();
-            }
-            if self.snake.head().eating {
-                self.score += 1;
+    if let Some(Command::Lockstep {
+        listen,
+        connect,
+        width,
+        height,
+    }) = &args.command
+    {
+        let peer = match (listen, connect) {
+            (Some(port), _) => {
+                let listener = TcpListener::bind(("0.0.0.0", *port))?;
+                println!("Waiting for the other player on port {port}");
+                Peer::listen(&listener, (*width, *height), step_length, stdout())
             }
-            self.add_snake_to_board();
-            self.draw(&format!(
-                "step time: {} us\n\r\
-            snake length: {}\n\r\
-            score: {}",
-                step_time.as_micros(),
-                self.snake.len(),
-                self.score
-            ))?;
-            self.remove_snake_from_board();
-            let head_direction = match self.snake.head_mut().snake_tile_type {
-                SnakePart::Head(ref mut direction) => direction,
-                _ => unreachable!(),
-            };
-            step_time = timer.elapsed().unwrap();
-            std::thread::sleep(GAME_STEP_LENGTH);
-
-            if event::poll(Duration::ZERO /*from_millis(10)*/)? {
-                match event::read()? {
-                    Event::Key(KeyEvent { code: key, .. }) => match key {
-                        KeyCode::Char(c) => {
-                            // \r - return to line start
-                            // \n - start a new line
-                            print!("\n\rinput: {c}\n\r");
-                            match c {
-                                'q' => break,
-                                'w' if *head_direction != Direction::Down => {
-                                    *head_direction = Direction::Up
-                                }
-                                'a' if *head_direction != Direction::Right => {
-                                    *head_direction = Direction::Left
-                                }
-                                's' if *head_direction != Direction::Up => {
-                                    *head_direction = Direction::Down
-                                }
-                                'd' if *head_direction != Direction::Left => {
-                                    *head_direction = Direction::Right
-                                }
-                                _ => {
-                                    print!("\n\rIgnored user input.\n\r");
-                                    std::thread::sleep(Duration::from_secs(1));
-                                }
-                            }
-                        }
-                        KeyCode::Up if *head_direction != Direction::Down => {
-                            *head_direction = Direction::Up
-                        }
-                        KeyCode::Left if *head_direction != Direction::Right => {
-                            *head_direction = Direction::Left
-                        }
-                        KeyCode::Down if *head_direction != Direction::Up => {
-                            *head_direction = Direction::Down
-                        }
-                        KeyCode::Right if *head_direction != Direction::Left => {
-                            *head_direction = Direction::Right
-                        }
-                        _ => {
-                            print!("\n\rIgnored user input.\n\r");
-                            std::thread::sleep(Duration::from_secs(1));
-                        }
-                    },
-                    Event::Resize(x, y) => {
-                        print!("new terminal size: {x}, {y}\n\r");
-                        std::thread::sleep(Duration::from_secs(1));
-                    }
-                    _ => unreachable!(),
-                }
+            (None, Some(address)) => Peer::connect(&with_port(address), stdout()),
+            (None, None) => unreachable!(),
+        };
+        let mut peer = match peer {
+            Ok(peer) => peer,
+            Err(err) => {
+                eprintln!("Could not start the game: {err}");
+                return Ok(());
             }
-
-            // self.snake = self.move_snake();
-            self.move_snake_experimental();
+        };
+        peer.set_key_bindings(keys);
+        peer.run()?;
+        match peer.game().replay().save(&args.replay_dir) {
+            Ok(path) => println!("Replay saved to {}", path.display()),
+            Err(err) => eprintln!("Could not save the replay: {err}"),
         }
 
-        Ok(())
-    }
-
-    fn add_snake_to_board(&mut self) {
-        for tile in self.snake.whole_snake() {
-            self.board[usize::from(tile.y)][usize::from(tile.x)] =
-                Tile::SnakePart(tile.snake_tile_type, tile.eating);
-        }
+        return Ok(());
     }
-
-    fn remove_snake_from_board(&mut self) {
-        for tile in self.snake.whole_snake() {
-            self.board[usize::from(tile.y)][usize::from(tile.x)] = Tile::Empty;
+    if let Some(Command::Tournament {
+        bots,
+        games,
+        seed,
+        max_ticks,
+        width,
+        height,
+        summary,
+    }) = args.command
+    {
+        if bots
+            .iter()
+            .any(|bot| matches!(bot, ControllerKind::Keyboard))
+        {
+            eprintln!("Keyboard controlled snakes cannot take part in a tournament");
+            return Ok(());
         }
-    }
-
-    // player controls already applied to the head
-    // also if possible this should be simplified
-    fn move_snake(&mut self) -> Snake {
-        let mut res = Snake::new();
-
-        // process snake head
-        let SnakeTile {
-            snake_tile_type,
-            mut x,
-            mut y,
-            eating: _,
-        } = self.snake.head();
-        match snake_tile_type {
-            SnakePart::Head(direction) => match direction {
-                Direction::Up => y.dec(),
-                Direction::Right => x.inc(),
-                Direction::Down => y.inc(),
-                Direction::Left => x.dec(),
-            },
-            _ => unreachable!(),
+        let tournament = tournament::Tournament {
+            bots,
+            games,
+            seed,
+            max_ticks,
+            width,
+            height,
+            move_time_limit,
         };
-        let eating = self.board[usize::from(y)][usize::from(x)].has_food();
-        res.body.push(SnakeTile {
-            x,
-            y,
-            snake_tile_type: *snake_tile_type,
-            eating,
-        });
-
-        let snake = self.snake.whole_snake();
-        for i in 1..=(snake.len() - 2) {
-            let previous_tile = snake[i - 1];
-            let previous_tile_type = previous_tile.snake_tile_type;
-            let SnakeTile {
-                mut snake_tile_type,
-                mut x,
-                mut y,
-                eating: _,
-            } = snake[i];
-
-            match snake_tile_type {
-                SnakePart::Body(ref mut direction) => match direction {
-                    BodyPartDirection::Up
-                    | BodyPartDirection::BottomLeftCornerUp
-                    | BodyPartDirection::BottomRightCornerUp => {
-                        y.dec();
-                        match previous_tile_type {
-                            SnakePart::Head(dir) => match dir {
-                                Direction::Up => *direction = BodyPartDirection::Up,
-                                Direction::Right => {
-                                    *direction = BodyPartDirection::TopLeftCornerRight
-                                }
-                                Direction::Left => {
-                                    *direction = BodyPartDirection::TopRightCornerLeft
-                                }
-                                Direction::Down => unreachable!(),
-                            },
-                            SnakePart::Body(dir) => *direction = dir,
-                            SnakePart::Tail(_) => unreachable!(),
-                        }
-                    }
-                    BodyPartDirection::Down
-                    | BodyPartDirection::TopLeftCornerDown
-                    | BodyPartDirection::TopRightCornerDown => {
-                        y.inc();
-                        match previous_tile_type {
-                            SnakePart::Head(dir) => match dir {
-                                Direction::Down => *direction = BodyPartDirection::Down,
-                                Direction::Right => {
-                                    *direction = BodyPartDirection::BottomLeftCornerRight
-                                }
-                                Direction::Left => {
-                                    *direction = BodyPartDirection::BottomRightCornerLeft
-                                }
-                                Direction::Up => unreachable!(),
-                            },
-                            SnakePart::Body(dir) => *direction = dir,
-                            SnakePart::Tail(_) => unreachable!(),
-                        }
-                    }
-                    BodyPartDirection::Left
-                    | BodyPartDirection::TopRightCornerLeft
-                    | BodyPartDirection::BottomRightCornerLeft => {
-                        x.dec();
-                        match previous_tile_type {
-                            SnakePart::Head(dir) => match dir {
-                                Direction::Up => *direction = BodyPartDirection::BottomLeftCornerUp,
-                                Direction::Down => {
-                                    *direction = BodyPartDirection::TopLeftCornerDown
-                                }
-                                Direction::Left => *direction = BodyPartDirection::Left,
-                                Direction::Right => unreachable!(),
-                            },
-                            SnakePart::Body(dir) => *direction = dir,
-                            SnakePart::Tail(_) => unreachable!(),
-                        }
-                    }
-                    BodyPartDirection::Right
-                    | BodyPartDirection::TopLeftCornerRight
-                    | BodyPartDirection::BottomLeftCornerRight => {
-                        x.inc();
-                        match previous_tile_type {
-                            SnakePart::Head(dir) => match dir {
-                                Direction::Up => {
-                                    *direction = BodyPartDirection::BottomRightCornerUp
-                                }
-                                Direction::Down => {
-                                    *direction = BodyPartDirection::TopRightCornerDown
-                                }
-                                Direction::Right => *direction = BodyPartDirection::Right,
-                                Direction::Left => unreachable!(),
-                            },
-                            SnakePart::Body(dir) => *direction = dir,
-                            SnakePart::Tail(_) => unreachable!(),
-                        }
-                    }
-                },
-                SnakePart::Tail(_) => unreachable!(),
-                SnakePart::Head(_) => unreachable!(),
+        let results = tournament.run()?;
+        match summary {
+            Some(path) if path.as_os_str() == "-" => {
+                println!("{}", serde_json::to_string_pretty(&results)?)
             }
-
-            res.body.push(SnakeTile {
-                x,
-                y,
-                snake_tile_type,
-                eating: previous_tile.eating,
-            });
+            Some(path) => {
+                tournament::print_leaderboard(&results);
+                std::fs::write(path, serde_json::to_string_pretty(&results)?)?;
+            }
+            None => tournament::print_leaderboard(&results),
         }
 
-        // process snake tail
-        let previous_tile = snake[snake.len() - 2];
-        // tail
-        let SnakeTile {
-            snake_tile_type: mut tail_tile_type,
-            mut x,
-            mut y,
-            eating: tail_eating,
-        } = snake.last().unwrap();
-
-        if *tail_eating {
-            res.body.push(previous_tile);
-            res.body.push(SnakeTile {
-                x,
-                y,
-                snake_tile_type: tail_tile_type,
-                eating: false,
-            });
-        } else {
-            match tail_tile_type {
-                SnakePart::Tail(ref mut direction) => match direction {
-                    Direction::Up => {
-                        y.dec();
-                        match previous_tile.snake_tile_type {
-                            SnakePart::Body(dir) => match dir {
-                                BodyPartDirection::Up => (),
-                                BodyPartDirection::TopLeftCornerRight => {
-                                    *direction = Direction::Right
-                                }
-                                BodyPartDirection::TopRightCornerLeft => {
-                                    *direction = Direction::Left
-                                }
-                                _ => unreachable!(),
-                            },
-                            _ => unreachable!(),
-                        }
-                    }
-                    Direction::Right => {
-                        x.inc();
-                        match previous_tile.snake_tile_type {
-                            SnakePart::Body(dir) => match dir {
-                                BodyPartDirection::Right => (),
-                                BodyPartDirection::BottomRightCornerUp => {
-                                    *direction = Direction::Up
-                                }
-                                BodyPartDirection::TopRightCornerDown => {
-                                    *direction = Direction::Down
-                                }
-                                _ => unreachable!(),
-                            },
-                            _ => unreachable!(),
+        return Ok(());
+    }
+
+    let mut settings = config.settings.clone();
+    settings.step_length = step_length.as_millis() as u64;
+
+    // without a game set up on the command line, the menu sets them up
+    if args.resume.is_none() && !args.autopilot && args.snake.is_empty() {
+        let mut menu = Menu::new(
+            stdout(),
+            config,
+            args.config.clone(),
+            args.scores_file.clone(),
+            args.daily_file.clone(),
+            args.campaign_file.clone(),
+            args.replay_dir.clone(),
+        );
+        loop {
+            match menu.run()? {
+                Choice::NewGame(settings) => {
+                    let kinds = settings.mode.controllers();
+                    let rules = settings.mode.rules();
+                    let seed = rand::random();
+                    let lines = match new_game(&args, &kinds, rules, &settings, &keys, seed, None) {
+                        Ok(mut game) => {
+                            play(&args, &mut game, kinds, Origin::Free, &settings, &keys)?
                         }
+                        Err(err) => vec![err],
+                    };
+                    if !lines.is_empty() {
+                        menu.set_message(lines.join("\n\r"));
                     }
-                    Direction::Down => {
-                        y.inc();
-                        match previous_tile.snake_tile_type {
-                            SnakePart::Body(dir) => match dir {
-                                BodyPartDirection::Down => (),
-                                BodyPartDirection::BottomLeftCornerRight => {
-                                    *direction = Direction::Right
-                                }
-                                BodyPartDirection::BottomRightCornerLeft => {
-                                    *direction = Direction::Left
-                                }
-                                _ => unreachable!(),
-                            },
-                            _ => unreachable!(),
-                        }
+                }
+                Choice::Daily(challenge, settings) => {
+                    let lines = play_daily(&args, &challenge, &settings, &keys)?;
+                    if !lines.is_empty() {
+                        menu.set_message(lines.join("\n\r"));
                     }
-                    Direction::Left => {
-                        x.dec();
-                        match previous_tile.snake_tile_type {
-                            SnakePart::Body(dir) => match dir {
-                                BodyPartDirection::Left => (),
-                                BodyPartDirection::BottomLeftCornerUp => *direction = Direction::Up,
-                                BodyPartDirection::TopLeftCornerDown => {
-                                    *direction = Direction::Down
-                                }
-                                _ => unreachable!(),
-                            },
-                            _ => unreachable!(),
-                        }
+                }
+                Choice::Level(index, settings) => {
+                    let lines = play_level(&args, index, &settings, &keys)?;
+                    if !lines.is_empty() {
+                        menu.set_message(lines.join("\n\r"));
                     }
+                }
+                Choice::Replay(path) => match Replay::load(&path) {
+                    Ok(replay) => Viewer::new(replay, stdout(), keys.clone()).run()?,
+                    Err(err) => menu.set_message(format!(
+                        "Could not load the replay {}: {err}",
+                        path.display()
+                    )),
                 },
-                _ => unreachable!(),
+                Choice::Quit => return Ok(()),
             }
-
-            res.body.push(SnakeTile {
-                x,
-                y,
-                snake_tile_type: tail_tile_type,
-                eating: previous_tile.eating,
-            });
         }
-
-        res
     }
 
-    fn move_snake_experimental(&mut self) {
-        // move head
-        let SnakeTile {
-            mut x,
-            mut y,
-            snake_tile_type,
-            eating: mut head_eating,
-        } = self.snake.body[0];
-
-        let old_head_x = x;
-        let old_head_y = y;
-        let head_direction = match snake_tile_type {
-            SnakePart::Head(direction) => match direction {
-                Direction::Up => {
-                    y.dec();
-                    Direction::Up
-                }
-                Direction::Right => {
-                    x.inc();
-                    Direction::Right
-                }
-                Direction::Down => {
-                    y.inc();
-                    Direction::Down
+    // what drives each snake, kept to create the controllers
+    // again when a saved game is resumed, and what the game was started as
+    let kinds: Vec<String>;
+    let origin: Origin;
+    let mut game = if let Some(path) = &args.resume {
+        let saved: SavedGame<GameRng> = match SavedGame::load(path) {
+            Ok(saved) => saved,
+            Err(err) => {
+                eprintln!("Could not load the saved game {}: {err}", path.display());
+                return Ok(());
+            }
+        };
+        let board = saved.game.board();
+        let (width, height) = (board[0].len(), board.len());
+        let boundary = saved.game.boundary();
+        let controllers = match create_controllers(
+            &saved.controllers,
+            (width, height, boundary),
+            move_time_limit,
+            &keys,
+        ) {
+            Ok(controllers) => controllers,
+            Err(err) => {
+                eprintln!("{err}");
+                return Ok(());
+            }
+        };
+        if let Origin::Daily(date) = &saved.origin {
+            match DailyResults::load(&args.daily_file) {
+                Ok(results) if results.is_finished(date) => {
+                    eprintln!("The daily challenge of {date} was already played");
+                    return Ok(());
                 }
-                Direction::Left => {
-                    x.dec();
-                    Direction::Left
+                Ok(_) => {}
+                Err(err) => {
+                    eprintln!("Could not load the daily results: {err}");
+                    return Ok(());
                 }
-            },
-            _ => unreachable!(),
+            }
+        }
+        kinds = saved.controllers.clone();
+        origin = saved.origin.clone();
+        let out = out(&args, width, height, kinds.len())?;
+        let game = match saved.resume(out, controllers) {
+            Ok(game) => game,
+            Err(err) => {
+                eprintln!("Could not resume the saved game: {err}");
+                return Ok(());
+            }
         };
-        let old_head_eating = head_eating;
-        head_eating = self.board[usize::from(y)][usize::from(x)].has_food();
-
-        // push all snake tiles forward in snake vec
-        self.snake.body.rotate_right(1);
-
-        // copy tail
-        let tail = self.snake.body[0];
-        if tail.eating {
-            // push tail to the end
-            self.snake.body.push(SnakeTile {
-                eating: false,
-                ..tail
-            });
-        } else {
-            // tail replaces the last tile
-            let last = self.snake.body.last().unwrap();
-            let direction = match last.snake_tile_type {
-                SnakePart::Body(direction) => match direction {
-                    BodyPartDirection::BottomLeftCornerRight
-                    | BodyPartDirection::Right
-                    | BodyPartDirection::TopLeftCornerRight => Direction::Right,
-                    BodyPartDirection::BottomLeftCornerUp
-                    | BodyPartDirection::Up
-                    | BodyPartDirection::BottomRightCornerUp => Direction::Up,
-                    BodyPartDirection::Down
-                    | BodyPartDirection::TopLeftCornerDown
-                    | BodyPartDirection::TopRightCornerDown => Direction::Down,
-                    BodyPartDirection::Left
-                    | BodyPartDirection::TopRightCornerLeft
-                    | BodyPartDirection::BottomRightCornerLeft => Direction::Left,
-                },
-                _ => unreachable!(),
-            };
-
-            *self.snake.body.last_mut().unwrap() = SnakeTile {
-                x: last.x,
-                y: last.y,
-                snake_tile_type: SnakePart::Tail(direction),
-                eating: last.eating,
-            };
+        if let Err(err) = saved.use_up(path) {
+            eprintln!("Could not remove the saved game {}: {err}", path.display());
+            return Ok(());
         }
 
-        // move head to the start
-        self.snake.body[0] = SnakeTile {
-            x,
-            y,
-            snake_tile_type,
-            eating: head_eating,
+        game
+    } else {
+        origin = Origin::Free;
+        kinds = if args.autopilot {
+            vec!["autopilot".to_owned()]
+        } else {
+            args.snake.iter().map(ToString::to_string).collect()
         };
+        match new_game(
+            &args,
+            &kinds,
+            Rules::Classic,
+            &settings,
+            &keys,
+            rand::random(),
+            None,
+        ) {
+            Ok(game) => game,
+            Err(err) => {
+                eprintln!("{err}");
+                return Ok(());
+            }
+        }
+    };
 
-        // add tile after the head that connects the head
-        // to the body
-        let end_tile_type = self.snake.body[2].snake_tile_type;
-        let direction = match end_tile_type {
-            SnakePart::Body(direction) => match direction {
-                BodyPartDirection::BottomLeftCornerRight
-                | BodyPartDirection::Right
-                | BodyPartDirection::TopLeftCornerRight => match head_direction {
-                    Direction::Up => BodyPartDirection::BottomRightCornerUp,
-                    Direction::Down => BodyPartDirection::TopRightCornerDown,
-                    Direction::Right => BodyPartDirection::Right,
-                    Direction::Left => unreachable!(),
-                },
-                BodyPartDirection::BottomLeftCornerUp
-                | BodyPartDirection::Up
-                | BodyPartDirection::BottomRightCornerUp => match head_direction {
-                    Direction::Up => BodyPartDirection::Up,
-                    Direction::Left => BodyPartDirection::TopRightCornerLeft,
-                    Direction::Right => BodyPartDirection::TopLeftCornerRight,
-                    Direction::Down => unreachable!(),
-                },
-                BodyPartDirection::Down
-                | BodyPartDirection::TopLeftCornerDown
-                | BodyPartDirection::TopRightCornerDown => match head_direction {
-                    Direction::Left => BodyPartDirection::BottomRightCornerLeft,
-                    Direction::Right => BodyPartDirection::BottomLeftCornerRight,
-                    Direction::Down => BodyPartDirection::Down,
-                    Direction::Up => unreachable!(),
-                },
-                BodyPartDirection::Left
-                | BodyPartDirection::TopRightCornerLeft
-                | BodyPartDirection::BottomRightCornerLeft => match head_direction {
-                    Direction::Up => BodyPartDirection::BottomLeftCornerUp,
-                    Direction::Down => BodyPartDirection::TopLeftCornerDown,
-                    Direction::Left => BodyPartDirection::Left,
-                    Direction::Right => unreachable!(),
-                },
-            },
-            _ => unreachable!(),
-        };
+    let mut lines = play(&args, &mut game, kinds, origin.clone(), &settings, &keys)?;
+    match origin {
+        Origin::Free => {}
+        Origin::Daily(date) => lines.extend(finish_daily(&args.daily_file, &date, &game)),
+        Origin::Level(index) => lines.extend(finish_level(&args.campaign_file, index, &game)),
+    }
+    for line in lines {
+        println!("{line}");
+    }
 
-        self.snake.body[1] = SnakeTile {
-            x: old_head_x,
-            y: old_head_y,
-            snake_tile_type: SnakePart::Body(direction),
-            eating: old_head_eating,
+    Ok(())
+}
+
+// the terminal, also recorded with --cast
+fn out(args: &Args, width: usize, height: usize, snakes: usize) -> Result<Box<dyn Write>> {
+    Ok(match &args.cast {
+        Some(path) => Box::new(CastRecorder::create(
+            stdout(),
+            path,
+            cast::terminal_size(width, height, snakes),
+        )?),
+        None => Box::new(stdout()),
+    })
+}
+
+// a game set up as in the settings, with a snake for each controller
+// kind and the obstacles, portals, hazards and target of the level, if any
+fn new_game(
+    args: &Args,
+    kinds: &[String],
+    rules: Rules,
+    settings: &Settings,
+    keys: &KeyBindings,
+    seed: u64,
+    level: Option<&Level>,
+) -> std::result::Result<SnakeGame<GameRng, Box<dyn Write>>, String> {
+    let (width, height) = (settings.width, settings.height);
+    // nothing kills in zen, so there are no walls to run into either
+    let boundary = if rules == Rules::Zen {
+        Boundary::Wrap
+    } else {
+        settings.boundary
+    };
+    let move_time_limit = Duration::from_millis(args.move_time_limit);
+    let controllers = create_controllers(kinds, (width, height, boundary), move_time_limit, keys)?;
+    let out = out(args, width, height, kinds.len())
+        .map_err(|err| format!("Could not record the game: {err}"))?;
+
+    let mut game = SnakeGame::with_seed(width, height, out, seed);
+    game.set_step_length(Duration::from_millis(settings.step_length));
+    game.set_boundary(boundary);
+    game.set_rules(rules);
+    game.set_power_ups(settings.power_ups);
+    if let Some(level) = level {
+        game.set_target(Some(level.target));
+        game.add_obstacles(&level.obstacles());
+        game.add_portals(&level.portals());
+        game.add_hazards(&level.hazards());
+    }
+    for (i, controller) in controllers.into_iter().enumerate() {
+        let snake = if kinds == ["autopilot"] {
+            solver::HamiltonianSolver::new(width, height, boundary)
+                .unwrap()
+                .initial_snake(6)
+        } else {
+            Snake::spawn(i, kinds.len(), width, height)
         };
+        game.add_snake(snake, controller);
     }
+    game.spawn_food();
 
-    // adds one food particle at random location
-    // food is only added to empty tile
-    fn add_food(&mut self) {
-        if self.count_food_on_board() >= MAX_FOOD_ON_BOARD || self.is_board_full() {
-            return;
-        }
+    Ok(game)
+}
 
-        let height = self.board_height;
-        let width = self.board_width;
-        let (a, b): (usize, usize) = self.rng.gen();
-        let (mut x, mut y) = (a % width, b % height);
+// plays the game until it is over or left, returns the lines telling
+// what became of it
+fn play(
+    args: &Args,
+    game: &mut SnakeGame<GameRng, Box<dyn Write>>,
+    kinds: Vec<String>,
+    origin: Origin,
+    settings: &Settings,
+    keys: &KeyBindings,
+) -> Result<Vec<String>> {
+    if let Some(port) = args.spectator_port {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let board = game.board();
+        let size = (board[0].len(), board.len());
+        game.set_spectators(net::Spectators::listen(listener, size));
+    }
 
-        if self.board[y][x] == Tile::Empty {
-            self.board[y][x] = Tile::Food(FoodType::Blob);
-        } else {
-            while self.board[y][x] != Tile::Empty {
-                x = self.rng.gen::<usize>() % width;
-                y = self.rng.gen::<usize>() % height;
+    game.set_key_bindings(keys.clone());
+    game.set_theme(settings.theme.as_deref().and_then(Theme::by_name).cloned());
+    game.set_up_screen()?;
+    let exit = game.play()?;
+    game.tear_down_screen()?;
+
+    let mut lines = Vec::new();
+    match exit {
+        Exit::Quit if game.rules() != Rules::Zen => {}
+        // zen games only end by leaving them
+        Exit::GameOver | Exit::Quit => {
+            lines.push(match game.replay().save(&args.replay_dir) {
+                Ok(path) => format!("Replay saved to {}", path.display()),
+                Err(err) => format!("Could not save the replay: {err}"),
+            });
+            // levels are not about the score
+            if kinds == ["keyboard"] && game.target().is_none() {
+                lines.extend(record_score(&args.scores_file, game));
             }
+        }
+        Exit::Save => lines.push(
+            match SavedGame::new(game, kinds, origin).save(&args.save_file) {
+                Ok(()) => format!(
+                    "Game saved, continue it with --resume {}",
+                    args.save_file.display()
+                ),
+                Err(err) => format!("Could not save the game: {err}"),
+            },
+        ),
+    }
+
+    Ok(lines)
+}
+
+// the one attempt of the day at the challenge, which is recorded
+// before the game starts
+fn play_daily(
+    args: &Args,
+    challenge: &Challenge,
+    settings: &Settings,
+    keys: &KeyBindings,
+) -> Result<Vec<String>> {
+    let path = &args.daily_file;
+    let mut results = match DailyResults::load(path) {
+        Ok(results) => results,
+        Err(err) => return Ok(vec![format!("Could not load the daily results: {err}")]),
+    };
+    let kinds = vec!["keyboard".to_owned()];
+    let mut game = match new_game(
+        args,
+        &kinds,
+        challenge.rules,
+        settings,
+        keys,
+        challenge.seed,
+        None,
+    ) {
+        Ok(game) => game,
+        Err(err) => return Ok(vec![err]),
+    };
+    if !results.record(&challenge.date, DailyResult::default()) {
+        return Ok(vec![format!(
+            "The daily challenge of {} was already played",
+            challenge.date
+        )]);
+    }
+    if let Err(err) = results.save(path) {
+        return Ok(vec![format!("Could not save the daily results: {err}")]);
+    }
+
+    let origin = Origin::Daily(challenge.date.clone());
+    let mut lines = play(args, &mut game, kinds, origin, settings, keys)?;
+    lines.extend(finish_daily(path, &challenge.date, &game));
+
+    Ok(lines)
+}
 
-            self.board[y][x] = Tile::Food(FoodType::Blob);
+// records the result of the daily challenge once its game is over or
+// saved, also after resuming it
+fn finish_daily<W: Write>(path: &Path, date: &str, game: &SnakeGame<GameRng, W>) -> Vec<String> {
+    let snake = &game.snakes()[0];
+    let result = DailyResult {
+        score: snake.score(),
+        length: snake.len(),
+        finished: game.state() != GameState::Running,
+    };
+    let mut lines = vec![format!("Daily challenge score: {}", result.score)];
+    let mut results = match DailyResults::load(path) {
+        Ok(results) => results,
+        Err(err) => {
+            lines.push(format!("Could not load the daily results: {err}"));
+            return lines;
         }
+    };
+    if !results.record(date, result) {
+        lines.push(format!(
+            "The daily challenge of {date} was already played, the score does not count"
+        ));
+    } else if let Err(err) = results.save(path) {
+        lines.push(format!("Could not save the daily results: {err}"));
     }
 
-    fn draw(&mut self, additional_text: &str) -> Result<()> {
-        let width = self.board_width;
-
-        // top line of the board
-        let top = "╔".to_owned() + &"═".repeat(width) + "╗";
-        let bottom = "╚".to_owned() + &"═".repeat(width) + "╝";
-        self.out
-            .queue(terminal::Clear(ClearType::All))?
-            .queue(cursor::MoveTo(0, 0))?
-            .queue(Print(format!("{top}\n\r")))?;
-
-        for row in &self.board {
-            self.out.queue(Print(format!(
-                "║{}║\n\r",
-                row.iter()
-                    .fold(String::with_capacity(width), |mut line, tile| {
-                        write!(&mut line, "{}", get_char(tile)).unwrap();
-                        line
-                    })
-            )))?;
+    lines
+}
+
+// plays a campaign level and saves it as completed when it is won
+fn play_level(
+    args: &Args,
+    index: usize,
+    settings: &Settings,
+    keys: &KeyBindings,
+) -> Result<Vec<String>> {
+    let kinds = vec!["keyboard".to_owned()];
+    let level = &LEVELS[index];
+    let mut game = match new_game(
+        args,
+        &kinds,
+        Rules::Classic,
+        settings,
+        keys,
+        rand::random(),
+        Some(level),
+    ) {
+        Ok(game) => game,
+        Err(err) => return Ok(vec![err]),
+    };
+
+    let mut lines = play(args, &mut game, kinds, Origin::Level(index), settings, keys)?;
+    lines.extend(finish_level(&args.campaign_file, index, &game));
+
+    Ok(lines)
+}
+
+// saves the level as completed when its game was won, also after resuming it
+fn finish_level<W: Write>(path: &Path, index: usize, game: &SnakeGame<GameRng, W>) -> Vec<String> {
+    if game.state() != GameState::Won {
+        return Vec::new();
+    }
+    let mut progress = match Progress::load(path) {
+        Ok(progress) => progress,
+        Err(err) => return vec![format!("Could not load the campaign progress: {err}")],
+    };
+    let mut lines = Vec::new();
+    if progress.complete(index) {
+        lines.push(match LEVELS.get(index + 1) {
+            Some(next) => format!(
+                "Level {} complete, level {} ({}) is unlocked!",
+                index + 1,
+                index + 2,
+                next.name
+            ),
+            None => "The last level is complete, well done!".to_owned(),
+        });
+        if let Err(err) = progress.save(path) {
+            lines.push(format!("Could not save the campaign progress: {err}"));
         }
+    }
 
-        // bottom line
-        self.out
-            .queue(Print(format!(
-                "{bottom}\n\r\
-         Control the snake with W,A,S,D or arrow keys\n\r\
-         {additional_text}\n\r\
-         Press q to exit..."
-            )))?
-            .flush()?;
-
-        Ok(())
+    lines
+}
+
+// adds a finished single player game to the high scores,
+// returns a line if it made it into the table
+fn record_score<W: Write>(path: &Path, game: &SnakeGame<GameRng, W>) -> Option<String> {
+    let snake = &game.snakes()[0];
+    if snake.score() == 0 {
+        return None;
     }
+    let board = game.board();
+    let entry = HighScore {
+        score: snake.score(),
+        length: snake.len(),
+        width: board[0].len(),
+        height: board.len(),
+        boundary: game.boundary(),
+        date: scores::today(),
+    };
+
+    let mut scores = match HighScores::load(path) {
+        Ok(scores) => scores,
+        Err(err) => return Some(format!("Could not load the high scores: {err}")),
+    };
+    let place = scores.add(game.rules(), entry)?;
+    Some(match scores.save(path) {
+        Ok(()) => format!(
+            "New {} high score, place {}!",
+            game.rules().name(),
+            place + 1
+        ),
+        Err(err) => format!("Could not save the high score: {err}"),
+    })
+}
 
-    fn count_food_on_board(&self) -> usize {
-        self.board
-            .iter()
-            .flatten()
-            .filter(|tile| tile.has_food())
-            .count()
+// width or height of the board given on the command line
+fn board_size(arg: &str) -> std::result::Result<usize, String> {
+    let size = arg.parse().map_err(|err| format!("{err}"))?;
+    if (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size) {
+        Ok(size)
+    } else {
+        Err(format!(
+            "must be between {MIN_BOARD_SIZE} and {MAX_BOARD_SIZE}"
+        ))
     }
+}
 
-    fn is_board_full(&self) -> bool {
-        !self.board.iter().flatten().any(|tile| tile.is_empty())
+// `host` or `host:port`, the default port if none is given
+fn with_port(address: &str) -> String {
+    if address.contains(':') {
+        address.to_owned()
+    } else {
+        format!("{address}:{}", net::DEFAULT_PORT)
     }
 }
 
-// snake is drawn using Box Drawing Unicode char block
-fn get_char(tile: &Tile) -> char {
-    match *tile {
-        Tile::Empty => ' ',
-        Tile::Food(_) => '*',
-        // Tile::Obstacle => '@',
-        Tile::SnakePart(snake_part, eating) => match snake_part {
-            SnakePart::Head(direction) => match direction {
-                Direction::Right => {
-                    if eating {
-                        'e'
-                    } else {
-                        '>'
-                    }
-                }
-                Direction::Left => {
-                    if eating {
-                        'e'
-                    } else {
-                        '<'
-                    }
-                }
-                Direction::Up => {
-                    if eating {
-                        'e'
-                    } else {
-                        '⌃'
-                    }
-                }
-                Direction::Down => {
-                    if eating {
-                        'e'
-                    } else {
-                        '⌄'
-                    }
-                }
-            },
-            SnakePart::Tail(direction) => match direction {
-                Direction::Right => {
-                    if eating {
-                        'e'
-                    } else {
-                        '>'
-                    }
-                }
-                Direction::Left => {
-                    if eating {
-                        'e'
-                    } else {
-                        '<'
-                    }
-                }
-                Direction::Up => {
-                    if eating {
-                        'e'
-                    } else {
-                        '⌃'
-                    }
-                }
-                Direction::Down => {
-                    if eating {
-                        'e'
-                    } else {
-                        '⌄'
-                    }
-                }
-            },
-            SnakePart::Body(direction) => match direction {
-                BodyPartDirection::Up => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┃'
-                    }
-                }
-                BodyPartDirection::Down => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┃'
-                    }
-                }
-                BodyPartDirection::Left => {
-                    if eating {
-                        'e'
-                    } else {
-                        '━'
-                    }
-                }
-                BodyPartDirection::Right => {
-                    if eating {
-                        'e'
-                    } else {
-                        '━'
-                    }
-                }
-                BodyPartDirection::TopLeftCornerRight => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┏'
-                    }
-                }
-                BodyPartDirection::TopLeftCornerDown => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┏'
-                    }
-                }
-                BodyPartDirection::TopRightCornerLeft => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┓'
-                    }
-                }
-                BodyPartDirection::TopRightCornerDown => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┓'
-                    }
-                }
-                BodyPartDirection::BottomLeftCornerRight => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┗'
-                    }
-                }
-                BodyPartDirection::BottomLeftCornerUp => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┗'
-                    }
-                }
-                BodyPartDirection::BottomRightCornerLeft => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┛'
-                    }
-                }
-                BodyPartDirection::BottomRightCornerUp => {
-                    if eating {
-                        'e'
-                    } else {
-                        '┛'
-                    }
-                }
-            },
-        },
+// one controller per snake from `--snake` values or `autopilot`
+fn create_controllers(
+    kinds: &[String],
+    (width, height, boundary): (usize, usize, Boundary),
+    move_time_limit: Duration,
+    keys: &KeyBindings,
+) -> std::result::Result<Vec<Box<dyn Controller>>, String> {
+    // with two players on one keyboard, the first one gets the
+    // first player's keys and the second one the arrow keys
+    let keyboard_count = kinds.iter().filter(|kind| *kind == "keyboard").count();
+    if keyboard_count > 2 {
+        return Err("At most two snakes can be controlled with the keyboard".to_owned());
     }
+    let mut keyboards: Vec<_> = (0..keyboard_count)
+        .rev()
+        .map(|player| keys.keyboard(player, keyboard_count))
+        .collect();
+
+    kinds
+        .iter()
+        .map(|kind| {
+            if kind == "autopilot" {
+                return match solver::HamiltonianSolver::new(width, height, boundary) {
+                    Some(solver) => Ok(Box::new(solver) as Box<dyn Controller>),
+                    None => Err(format!(
+                        "No Hamiltonian cycle exists for a {width}x{height} board with {}",
+                        boundary.name()
+                    )),
+                };
+            }
+            match kind.parse()? {
+                ControllerKind::Keyboard => Ok(Box::new(keyboards.pop().unwrap()) as _),
+                kind => kind
+                    .create(move_time_limit)
+                    .map_err(|err| format!("Could not start '{kind}': {err}")),
+            }
+        })
+        .collect()
 }
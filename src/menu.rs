@@ -0,0 +1,663 @@
+use crate::{
+    campaign::{Progress, LEVELS},
+    config::{Action, Config, KeyBindings, Settings},
+    daily::{Challenge, DailyResults},
+    scores::{self, HighScores},
+    solver::HamiltonianSolver,
+    theme::THEMES,
+    Boundary, Rules,
+};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent},
+    style::Print,
+    terminal::{self, ClearType},
+    QueueableCommand, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Write as FmtWrite,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+// replays listed at once, the list scrolls with the selection
+const VISIBLE_REPLAYS: usize = 15;
+
+// who plays the games started from the menu and by which rules
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    #[default]
+    Classic,
+    TimeAttack,
+    Zen,
+    Survival,
+    TwoPlayers,
+    Bot,
+    Autopilot,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 7] = [
+        Mode::Classic,
+        Mode::TimeAttack,
+        Mode::Zen,
+        Mode::Survival,
+        Mode::TwoPlayers,
+        Mode::Bot,
+        Mode::Autopilot,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Mode::Classic => "classic, one player",
+            Mode::TimeAttack => "time attack, as many points as possible in a minute",
+            Mode::Zen => "zen, no dying, just growing",
+            Mode::Survival => "survival, food goes bad and the snake keeps shrinking",
+            Mode::TwoPlayers => "two players on one keyboard",
+            Mode::Bot => "against a bot",
+            Mode::Autopilot => "watch the autopilot",
+        }
+    }
+
+    // the controllers of the snakes, like --snake takes them
+    pub fn controllers(self) -> Vec<String> {
+        let kinds: &[&str] = match self {
+            Mode::TwoPlayers => &["keyboard", "keyboard"],
+            Mode::Bot => &["keyboard", "bot"],
+            Mode::Autopilot => &["autopilot"],
+            _ => &["keyboard"],
+        };
+
+        kinds.iter().map(|kind| kind.to_string()).collect()
+    }
+
+    // the games with more than one player are classic ones
+    pub fn rules(self) -> Rules {
+        match self {
+            Mode::TimeAttack => Rules::TimeAttack,
+            Mode::Zen => Rules::Zen,
+            Mode::Survival => Rules::Survival,
+            _ => Rules::Classic,
+        }
+    }
+}
+
+// what was picked in the menu
+pub enum Choice {
+    NewGame(Settings),
+    // the settings are the ones of the challenge
+    Daily(Challenge, Settings),
+    // the index into `LEVELS` and the settings of the level
+    Level(usize, Settings),
+    Replay(PathBuf),
+    Quit,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Item {
+    NewGame,
+    Daily,
+    Campaign,
+    Mode,
+    Settings,
+    HighScores,
+    Replays,
+    Quit,
+}
+
+const ITEMS: [Item; 8] = [
+    Item::NewGame,
+    Item::Daily,
+    Item::Campaign,
+    Item::Mode,
+    Item::Settings,
+    Item::HighScores,
+    Item::Replays,
+    Item::Quit,
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Setting {
+    Width,
+    Height,
+    Speed,
+    Theme,
+    Boundary,
+    PowerUps,
+    Back,
+}
+
+const SETTINGS: [Setting; 7] = [
+    Setting::Width,
+    Setting::Height,
+    Setting::Speed,
+    Setting::Theme,
+    Setting::Boundary,
+    Setting::PowerUps,
+    Setting::Back,
+];
+
+// keys as the menu understands them, from the arrow keys
+// and the keys steering the snakes
+#[derive(Clone, Copy, PartialEq)]
+enum MenuKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back,
+}
+
+// the screen shown when the game is started without a command, setting
+// up the games and leading to everything else
+pub struct Menu<W: Write> {
+    out: W,
+    config: Config,
+    // where changed settings are saved
+    config_path: PathBuf,
+    keys: KeyBindings,
+    scores_path: PathBuf,
+    daily_path: PathBuf,
+    campaign_path: PathBuf,
+    replay_dir: PathBuf,
+    selected: usize,
+    // shown below the menu until the next key, like how the last game ended
+    message: Option<String>,
+}
+
+impl<W: Write> Menu<W> {
+    pub fn new(
+        out: W,
+        config: Config,
+        config_path: PathBuf,
+        scores_path: PathBuf,
+        daily_path: PathBuf,
+        campaign_path: PathBuf,
+        replay_dir: PathBuf,
+    ) -> Self {
+        let keys = config.key_bindings();
+
+        Self {
+            out,
+            config,
+            config_path,
+            keys,
+            scores_path,
+            daily_path,
+            campaign_path,
+            replay_dir,
+            selected: 0,
+            message: None,
+        }
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    // shows the menu until something is picked that leaves it
+    pub fn run(&mut self) -> Result<Choice> {
+        self.out
+            .queue(cursor::Hide)?
+            .queue(terminal::EnterAlternateScreen)?
+            .flush()?;
+        terminal::enable_raw_mode()?;
+        let result = self.main_menu();
+        terminal::disable_raw_mode()?;
+        self.out
+            .queue(cursor::Show)?
+            .queue(terminal::LeaveAlternateScreen)?
+            .flush()?;
+
+        result
+    }
+
+    fn main_menu(&mut self) -> Result<Choice> {
+        loop {
+            let mut text = String::from("S N A K E\n\r\n\r");
+            for (i, item) in ITEMS.iter().enumerate() {
+                let label = match item {
+                    Item::NewGame => "New game".to_owned(),
+                    Item::Daily => "Daily challenge".to_owned(),
+                    Item::Campaign => "Campaign".to_owned(),
+                    Item::Mode => format!("Mode: {}", self.config.settings.mode.description()),
+                    Item::Settings => "Settings".to_owned(),
+                    Item::HighScores => "High scores".to_owned(),
+                    Item::Replays => "Replays".to_owned(),
+                    Item::Quit => "Quit".to_owned(),
+                };
+                write!(&mut text, "{}{label}\n\r", cursor_mark(i == self.selected)).unwrap();
+            }
+            text.push_str("\n\rUp and down to choose, enter to select");
+            if let Some(message) = self.message.take() {
+                write!(&mut text, "\n\r\n\r{message}").unwrap();
+            }
+            self.show(&text)?;
+
+            let item = ITEMS[self.selected];
+            let key = self.read_key()?;
+            match key {
+                MenuKey::Up => self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len(),
+                MenuKey::Down => self.selected = (self.selected + 1) % ITEMS.len(),
+                MenuKey::Left | MenuKey::Right if item == Item::Mode => {
+                    let settings = &mut self.config.settings;
+                    settings.mode = cycle(&Mode::ALL, settings.mode, key == MenuKey::Right);
+                    self.save_config();
+                }
+                MenuKey::Back => return Ok(Choice::Quit),
+                MenuKey::Select => match item {
+                    Item::NewGame => {
+                        let settings = &self.config.settings;
+                        if settings.mode == Mode::Autopilot
+                            && HamiltonianSolver::new(
+                                settings.width,
+                                settings.height,
+                                settings.boundary,
+                            )
+                            .is_none()
+                        {
+                            self.message = Some(
+                                "The autopilot cannot play on this board, with walls \
+                                 the width or the height has to be even"
+                                    .to_owned(),
+                            );
+                            continue;
+                        }
+                        return Ok(Choice::NewGame(settings.clone()));
+                    }
+                    Item::Mode => {
+                        let settings = &mut self.config.settings;
+                        settings.mode = cycle(&Mode::ALL, settings.mode, true);
+                        self.save_config();
+                    }
+                    Item::Daily => {
+                        if let Some(challenge) = self.daily()? {
+                            let settings = Settings {
+                                width: challenge.width,
+                                height: challenge.height,
+                                step_length: challenge.step_length,
+                                boundary: challenge.boundary,
+                                // the same game for everyone
+                                power_ups: false,
+                                ..self.config.settings.clone()
+                            };
+                            return Ok(Choice::Daily(challenge, settings));
+                        }
+                    }
+                    Item::Campaign => {
+                        if let Some(index) = self.campaign()? {
+                            let level = &LEVELS[index];
+                            let settings = Settings {
+                                width: level.width(),
+                                height: level.height(),
+                                step_length: level.step_length,
+                                boundary: level.boundary,
+                                power_ups: level.power_ups,
+                                ..self.config.settings.clone()
+                            };
+                            return Ok(Choice::Level(index, settings));
+                        }
+                    }
+                    Item::Settings => self.settings()?,
+                    Item::HighScores => self.high_scores()?,
+                    Item::Replays => {
+                        if let Some(path) = self.replays()? {
+                            return Ok(Choice::Replay(path));
+                        }
+                    }
+                    Item::Quit => return Ok(Choice::Quit),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn settings(&mut self) -> Result<()> {
+        let mut selected = 0;
+        loop {
+            let settings = &self.config.settings;
+            let mut text = String::from("Settings\n\r\n\r");
+            for (i, setting) in SETTINGS.iter().enumerate() {
+                let label = match setting {
+                    Setting::Width => format!("Board width: {}", settings.width),
+                    Setting::Height => format!("Board height: {}", settings.height),
+                    Setting::Speed => format!("Speed: {} ms per step", settings.step_length),
+                    Setting::Theme => format!(
+                        "Theme: {}",
+                        settings.theme.as_deref().unwrap_or("terminal colours")
+                    ),
+                    Setting::Boundary => format!(
+                        "Board edges: {}",
+                        match settings.boundary {
+                            _ if settings.mode == Mode::Zen => "wrap around in zen",
+                            Boundary::Wrap => "wrap around",
+                            Boundary::Walls => "walls",
+                        }
+                    ),
+                    Setting::PowerUps => format!(
+                        "Power-ups: {}",
+                        if settings.power_ups { "on" } else { "off" }
+                    ),
+                    Setting::Back => "Back".to_owned(),
+                };
+                write!(&mut text, "{}{label}\n\r", cursor_mark(i == selected)).unwrap();
+            }
+            text.push_str("\n\rUp and down to choose, left and right to change");
+            self.show(&text)?;
+
+            let key = self.read_key()?;
+            // enter changes the value like right does
+            let forward = key != MenuKey::Left;
+            let settings = &mut self.config.settings;
+            match (key, SETTINGS[selected]) {
+                (MenuKey::Up, _) => selected = (selected + SETTINGS.len() - 1) % SETTINGS.len(),
+                (MenuKey::Down, _) => selected = (selected + 1) % SETTINGS.len(),
+                (MenuKey::Back, _) | (MenuKey::Select, Setting::Back) => break,
+                (MenuKey::Left | MenuKey::Right | MenuKey::Select, setting) => match setting {
+                    Setting::Width => settings.width = change(settings.width, 1, 8..=100, forward),
+                    Setting::Height => {
+                        settings.height = change(settings.height, 1, 5..=50, forward)
+                    }
+                    Setting::Speed => {
+                        // faster is a shorter step
+                        settings.step_length = change(settings.step_length, 25, 50..=1000, !forward)
+                    }
+                    Setting::Theme => {
+                        let themes: Vec<_> = [None]
+                            .into_iter()
+                            .chain(THEMES.iter().map(|theme| Some(theme.name.to_owned())))
+                            .collect();
+                        settings.theme = cycle(&themes, settings.theme.clone(), forward);
+                    }
+                    // zen games always wrap, see new_game
+                    Setting::Boundary if settings.mode == Mode::Zen => {}
+                    Setting::Boundary => {
+                        settings.boundary = cycle(&Boundary::ALL, settings.boundary, forward)
+                    }
+                    Setting::PowerUps => settings.power_ups = !settings.power_ups,
+                    Setting::Back => {}
+                },
+            }
+        }
+        self.save_config();
+
+        Ok(())
+    }
+
+    // today's challenge, if it is going to be played
+    fn daily(&mut self) -> Result<Option<Challenge>> {
+        let challenge = Challenge::for_date(&scores::today());
+        let results = DailyResults::load(&self.daily_path);
+        let played = !matches!(&results, Ok(results) if results.get(&challenge.date).is_none());
+
+        let mut text = format!(
+            "Daily challenge {}\n\r\n\r{}\n\r\n\r",
+            challenge.date,
+            challenge.description()
+        );
+        match &results {
+            Ok(results) => {
+                match results.get(&challenge.date) {
+                    Some(result) if result.finished => write!(
+                        &mut text,
+                        "Today: score {}, length {}\n\r",
+                        result.score, result.length
+                    ),
+                    Some(_) => write!(&mut text, "Today: left before the end\n\r"),
+                    None => write!(&mut text, "Today: not played yet\n\r"),
+                }
+                .unwrap();
+                if let Some((date, best)) = results.best() {
+                    write!(&mut text, "Best day: {date} with score {}\n\r", best.score).unwrap();
+                }
+            }
+            Err(err) => write!(&mut text, "Could not load the results: {err}\n\r").unwrap(),
+        }
+        if played {
+            text.push_str("\n\rThere is one attempt per day, come back tomorrow!");
+        } else {
+            text.push_str("\n\rEnter to play, there is one attempt per day");
+        }
+        self.show(&text)?;
+
+        Ok(match self.read_key()? {
+            MenuKey::Select if !played => Some(challenge),
+            _ => None,
+        })
+    }
+
+    // the level picked to be played, None to go back; levels are locked
+    // until the one before is completed
+    fn campaign(&mut self) -> Result<Option<usize>> {
+        let progress = Progress::load(&self.campaign_path);
+        let unlocked =
+            |index: usize| matches!(&progress, Ok(progress) if progress.is_unlocked(index));
+        // the first level not completed yet
+        let mut selected = (0..LEVELS.len())
+            .find(|&index| {
+                matches!(&progress, Ok(progress) if !progress.is_completed(index))
+                    && unlocked(index)
+            })
+            .unwrap_or(0);
+        loop {
+            let mut text = String::from("Campaign\n\r\n\r");
+            for (i, level) in LEVELS.iter().enumerate() {
+                let status = match &progress {
+                    Ok(progress) if progress.is_completed(i) => "done",
+                    Ok(progress) if progress.is_unlocked(i) => "open",
+                    _ => "locked",
+                };
+                write!(
+                    &mut text,
+                    "{}{}. {:<12} {status:<7} {}\n\r",
+                    cursor_mark(i == selected),
+                    i + 1,
+                    level.name,
+                    level.description()
+                )
+                .unwrap();
+            }
+            if let Err(err) = &progress {
+                write!(&mut text, "\n\rCould not load the progress: {err}\n\r").unwrap();
+            }
+            text.push_str("\n\rEnter to play an open level, escape to go back");
+            self.show(&text)?;
+
+            match self.read_key()? {
+                MenuKey::Up => selected = selected.saturating_sub(1),
+                MenuKey::Down => selected = (selected + 1).min(LEVELS.len() - 1),
+                MenuKey::Select if unlocked(selected) => return Ok(Some(selected)),
+                MenuKey::Back => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+
+    // one table per rule set, left and right switch between them
+    fn high_scores(&mut self) -> Result<()> {
+        let scores = HighScores::load(&self.scores_path);
+        let mut rules = self.config.settings.mode.rules();
+        loop {
+            let mut text = format!("High scores: {}\n\r\n\r", rules.name());
+            match &scores {
+                Ok(scores) if scores.table(rules).is_empty() => {
+                    text.push_str("No games played yet\n\r");
+                }
+                Ok(scores) => {
+                    for (i, entry) in scores.table(rules).iter().enumerate() {
+                        write!(
+                            &mut text,
+                            "{:>2}. {:>5}  length {:<4} {}x{} {:<6} {}\n\r",
+                            i + 1,
+                            entry.score,
+                            entry.length,
+                            entry.width,
+                            entry.height,
+                            entry.boundary.name(),
+                            entry.date
+                        )
+                        .unwrap();
+                    }
+                }
+                Err(err) => {
+                    write!(&mut text, "Could not load the high scores: {err}\n\r").unwrap();
+                }
+            }
+            text.push_str("\n\rLeft and right for the other modes, escape to go back");
+            self.show(&text)?;
+
+            match self.read_key()? {
+                key @ (MenuKey::Left | MenuKey::Right) => {
+                    rules = cycle(&Rules::ALL, rules, key == MenuKey::Right)
+                }
+                MenuKey::Back | MenuKey::Select => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    // the replay picked to be watched, None to go back
+    fn replays(&mut self) -> Result<Option<PathBuf>> {
+        let replays = list_replays(&self.replay_dir);
+        let mut selected: usize = 0;
+        loop {
+            let mut text = String::from("Replays\n\r\n\r");
+            if replays.is_empty() {
+                write!(
+                    &mut text,
+                    "No replays in {} yet\n\r",
+                    self.replay_dir.display()
+                )
+                .unwrap();
+            }
+            let first = selected.saturating_sub(VISIBLE_REPLAYS - 1);
+            for (i, path) in replays.iter().enumerate().skip(first).take(VISIBLE_REPLAYS) {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                write!(&mut text, "{}{name}\n\r", cursor_mark(i == selected)).unwrap();
+            }
+            text.push_str("\n\rNewest first, enter to watch, escape to go back");
+            self.show(&text)?;
+
+            match self.read_key()? {
+                MenuKey::Up => selected = selected.saturating_sub(1),
+                MenuKey::Down => selected = (selected + 1).min(replays.len().saturating_sub(1)),
+                MenuKey::Select if !replays.is_empty() => {
+                    return Ok(Some(replays[selected].clone()))
+                }
+                MenuKey::Back | MenuKey::Select => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+
+    fn save_config(&mut self) {
+        if let Err(err) = self.config.save(&self.config_path) {
+            self.message = Some(format!(
+                "Could not save the settings to {}: {err}",
+                self.config_path.display()
+            ));
+        }
+    }
+
+    fn show(&mut self, text: &str) -> Result<()> {
+        self.out
+            .queue(terminal::Clear(ClearType::All))?
+            .queue(cursor::MoveTo(0, 0))?
+            .queue(Print(text))?
+            .flush()?;
+
+        Ok(())
+    }
+
+    fn read_key(&mut self) -> Result<MenuKey> {
+        loop {
+            if let Event::Key(KeyEvent { code, .. }) = event::read()? {
+                if let Some(key) = self.menu_key(code) {
+                    return Ok(key);
+                }
+            }
+        }
+    }
+
+    fn menu_key(&self, code: KeyCode) -> Option<MenuKey> {
+        match code {
+            KeyCode::Up => return Some(MenuKey::Up),
+            KeyCode::Down => return Some(MenuKey::Down),
+            KeyCode::Left => return Some(MenuKey::Left),
+            KeyCode::Right => return Some(MenuKey::Right),
+            KeyCode::Enter | KeyCode::Char(' ') => return Some(MenuKey::Select),
+            KeyCode::Esc => return Some(MenuKey::Back),
+            _ => {}
+        }
+        match self.keys.action(code)? {
+            Action::Up | Action::SecondUp => Some(MenuKey::Up),
+            Action::Down | Action::SecondDown => Some(MenuKey::Down),
+            Action::Left | Action::SecondLeft => Some(MenuKey::Left),
+            Action::Right | Action::SecondRight => Some(MenuKey::Right),
+            Action::Quit => Some(MenuKey::Back),
+            _ => None,
+        }
+    }
+}
+
+fn cursor_mark(selected: bool) -> &'static str {
+    if selected {
+        "> "
+    } else {
+        "  "
+    }
+}
+
+// the value before or after the current one, starting over at the ends
+fn cycle<T: Clone + PartialEq>(values: &[T], current: T, forward: bool) -> T {
+    let i = values
+        .iter()
+        .position(|value| *value == current)
+        .unwrap_or(0);
+    let next = if forward {
+        (i + 1) % values.len()
+    } else {
+        (i + values.len() - 1) % values.len()
+    };
+
+    values[next].clone()
+}
+
+// the value changed by one step, staying in the range
+fn change<T>(value: T, step: T, range: std::ops::RangeInclusive<T>, up: bool) -> T
+where
+    T: Copy + Ord + std::ops::Add<Output = T> + std::ops::Sub<Output = T>,
+{
+    let value = if up {
+        value + step
+    } else if value >= *range.start() + step {
+        value - step
+    } else {
+        *range.start()
+    };
+
+    value.clamp(*range.start(), *range.end())
+}
+
+// the replay files in the directory, newest first
+fn list_replays(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut replays: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .collect();
+    replays.sort_by(|a, b| b.cmp(a));
+
+    replays.into_iter().map(|(_, path)| path).collect()
+}
@@ -0,0 +1,668 @@
+use crate::{
+    check_board_size,
+    config::{Action, KeyBindings},
+    controller::{Controller, GameView},
+    Board, Boundary, Direction, GameRng, GameState, Snake, SnakeGame, Tile,
+};
+use crossterm::{
+    event::{self, Event, KeyEvent},
+    Result,
+};
+use rand::{Rng, SeedableRng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+pub const DEFAULT_PORT: u16 = 7878;
+// time a client has to say it joins after connecting
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+// a client not taking a step in this time is dropped
+// rather than holding up everyone else
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// sent by the server, one JSON object per line
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ServerMessage {
+    // the first message to a client, `you` is the index of the snake
+    // of a player and missing for spectators
+    Welcome {
+        you: Option<usize>,
+        width: usize,
+        height: usize,
+    },
+    // instead of the welcome when the client cannot take part
+    Refused {
+        reason: String,
+    },
+    // while the server waits for everyone to join
+    Lobby {
+        joined: usize,
+        players: usize,
+    },
+    // the game after every step
+    Update {
+        tick: u64,
+        state: GameState,
+        boundary: Boundary,
+        // everything on the board but the snakes and empty tiles:
+        // food, power-ups, obstacles, portals and hazards
+        tiles: Vec<((usize, usize), Tile)>,
+        snakes: Vec<Snake>,
+        scoreboard: String,
+    },
+}
+
+// sent by a client, one JSON object per line
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    // the first message after connecting, to play
+    Join,
+    // the first message after connecting, to only watch
+    Watch,
+    Turn { direction: Direction },
+}
+
+// one message as a line of JSON
+pub(crate) fn send<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+// messages read from the stream on a separate thread; the channel
+// is closed when the connection is or when a message is not understood
+pub(crate) fn receive<T: DeserializeOwned + Send + 'static>(stream: TcpStream) -> Receiver<T> {
+    let (sender, messages) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(|line| line.ok()) {
+            match serde_json::from_str(&line) {
+                Ok(message) => {
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    });
+
+    messages
+}
+
+// drives a snake by the directions a client sends over the network,
+// one per step like the keyboard; a client that disconnects or sends
+// anything else loses its snake
+pub struct RemoteController {
+    inputs: Receiver<Direction>,
+    disconnected: bool,
+}
+
+impl RemoteController {
+    // the snake is disconnected once the sender is dropped
+    pub(crate) fn new(inputs: Receiver<Direction>) -> Self {
+        Self {
+            inputs,
+            disconnected: false,
+        }
+    }
+
+    fn spawn(reader: BufReader<TcpStream>) -> Self {
+        // reading blocks, so it is done on a separate thread
+        let (sender, inputs) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines().map_while(|line| line.ok()) {
+                match serde_json::from_str(&line) {
+                    Ok(ClientMessage::Turn { direction }) => {
+                        if sender.send(direction).is_err() {
+                            break;
+                        }
+                    }
+                    _ => break,
+                }
+            }
+        });
+
+        Self::new(inputs)
+    }
+}
+
+impl Controller for RemoteController {
+    fn next_direction(&mut self, view: &GameView) -> Direction {
+        match self.inputs.try_recv() {
+            Ok(direction) => direction,
+            Err(TryRecvError::Empty) => view.snake().direction(),
+            Err(TryRecvError::Disconnected) => {
+                self.disconnected = true;
+                view.snake().direction()
+            }
+        }
+    }
+
+    fn disqualification(&self) -> Option<&str> {
+        self.disconnected.then_some("disconnected")
+    }
+}
+
+// a new client, after it said whether it wants to play or watch
+enum Connection {
+    // with the connection to read the player's moves from
+    Player(TcpStream, BufReader<TcpStream>),
+    Spectator(TcpStream),
+}
+
+// accepts clients on a separate thread, so that the game does not
+// have to wait for them; clients failing to say what they want are
+// passed on as errors
+fn accept(listener: TcpListener) -> Receiver<(SocketAddr, io::Result<Connection>)> {
+    let (sender, connections) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let Ok(address) = stream.peer_addr() else {
+                continue;
+            };
+            if sender.send((address, handshake(stream))).is_err() {
+                break;
+            }
+        }
+    });
+
+    connections
+}
+
+fn handshake(stream: TcpStream) -> io::Result<Connection> {
+    stream.set_nodelay(true)?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    stream.set_read_timeout(Some(JOIN_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    stream.set_read_timeout(None)?;
+
+    match serde_json::from_str(&line) {
+        Ok(ClientMessage::Join) => Ok(Connection::Player(stream, reader)),
+        Ok(ClientMessage::Watch) => Ok(Connection::Spectator(stream)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected a join or watch message",
+        )),
+    }
+}
+
+fn refuse(mut stream: TcpStream, reason: &str) {
+    let reason = reason.to_owned();
+    send(&mut stream, &ServerMessage::Refused { reason }).ok();
+}
+
+// clients watching a game: they get every step, but cannot steer
+pub struct Spectators {
+    size: (usize, usize),
+    clients: Vec<TcpStream>,
+    // new clients of a game that is not hosted with `serve`
+    connections: Option<Receiver<(SocketAddr, io::Result<Connection>)>>,
+}
+
+impl Spectators {
+    fn new(size: (usize, usize)) -> Self {
+        Self {
+            size,
+            clients: Vec::new(),
+            connections: None,
+        }
+    }
+
+    // lets clients watch a game on a board of the size from the listener,
+    // like a local game; clients wanting to play are turned away
+    pub fn listen(listener: TcpListener, size: (usize, usize)) -> Self {
+        Self {
+            connections: Some(accept(listener)),
+            ..Self::new(size)
+        }
+    }
+
+    fn add(&mut self, mut stream: TcpStream) {
+        let (width, height) = self.size;
+        let welcome = ServerMessage::Welcome {
+            you: None,
+            width,
+            height,
+        };
+        if send(&mut stream, &welcome).is_ok() {
+            self.clients.push(stream);
+        }
+    }
+
+    // spectators that cannot be written to are dropped
+    fn send(&mut self, message: &ServerMessage) {
+        while let Some(Ok((_, connection))) = self.connections.as_ref().map(Receiver::try_recv) {
+            match connection {
+                Ok(Connection::Spectator(stream)) => self.add(stream),
+                Ok(Connection::Player(stream, _)) => {
+                    refuse(stream, "this game can only be watched")
+                }
+                Err(_) => {}
+            }
+        }
+        self.clients
+            .retain_mut(|stream| send(stream, message).is_ok());
+    }
+}
+
+// the clients of a game hosted with `serve`
+struct Host {
+    players: usize,
+    // clients[i] plays snakes[i], None once it is gone
+    clients: Vec<Option<TcpStream>>,
+    spectators: Spectators,
+}
+
+impl Host {
+    // players get a snake until the game is full,
+    // everyone after that can only watch
+    fn admit(
+        &mut self,
+        game: &mut SnakeGame<GameRng, io::Sink>,
+        (address, connection): (SocketAddr, io::Result<Connection>),
+        log: &mut impl FnMut(String),
+    ) {
+        match connection {
+            Ok(Connection::Player(mut stream, reader)) if self.clients.len() < self.players => {
+                let you = self.clients.len();
+                let welcome = ServerMessage::Welcome {
+                    you: Some(you),
+                    width: game.board_width,
+                    height: game.board_height,
+                };
+                if let Err(err) = send(&mut stream, &welcome) {
+                    log(format!("{address} could not join: {err}"));
+                    return;
+                }
+                game.add_snake(
+                    Snake::spawn(you, self.players, game.board_width, game.board_height),
+                    Box::new(RemoteController::spawn(reader)),
+                );
+                self.clients.push(Some(stream));
+                log(format!("player {} joined from {address}", you + 1));
+                self.send(&ServerMessage::Lobby {
+                    joined: you + 1,
+                    players: self.players,
+                });
+            }
+            Ok(Connection::Player(stream, _)) => refuse(stream, "the game has already started"),
+            Ok(Connection::Spectator(stream)) => {
+                self.spectators.add(stream);
+                log(format!("{address} is watching"));
+            }
+            Err(err) => log(format!("{address} could not join: {err}")),
+        }
+    }
+
+    // a player that cannot be written to is disconnected,
+    // which removes its snake in the next step
+    fn send(&mut self, message: &ServerMessage) {
+        for client in &mut self.clients {
+            if let Some(stream) = client {
+                if send(stream, message).is_err() {
+                    stream.shutdown(Shutdown::Both).ok();
+                    *client = None;
+                }
+            }
+        }
+        self.spectators.send(message);
+    }
+}
+
+// hosts a game: waits for the players to join on the listener, then
+// runs the game, the only copy of it, and sends every step to all of
+// them and to any spectators; `log` is told who joins and leaves;
+// returns the finished game
+pub fn serve(
+    listener: TcpListener,
+    players: usize,
+    (width, height): (usize, usize),
+    step_length: Duration,
+    mut log: impl FnMut(String),
+) -> io::Result<SnakeGame<GameRng, io::Sink>> {
+    let mut game = SnakeGame::new(width, height, io::sink());
+    game.set_step_length(step_length);
+    let connections = accept(listener);
+    let mut host = Host {
+        players,
+        clients: Vec::new(),
+        spectators: Spectators::new((width, height)),
+    };
+
+    while host.clients.len() < players {
+        let connection = connections
+            .recv()
+            .map_err(|_| io::Error::other("stopped accepting clients"))?;
+        host.admit(&mut game, connection, &mut log);
+    }
+    game.spawn_food();
+    log("the game starts".to_owned());
+
+    loop {
+        while let Ok(connection) = connections.try_recv() {
+            host.admit(&mut game, connection, &mut log);
+        }
+        host.send(&update(&game));
+        if game.state != GameState::Running {
+            break;
+        }
+        thread::sleep(game.current_step_length());
+
+        let alive: Vec<_> = game.snakes.iter().map(|snake| snake.alive).collect();
+        game.step();
+        for (i, was_alive) in alive.into_iter().enumerate() {
+            if !was_alive || game.snakes[i].alive {
+                continue;
+            }
+            match game.controllers[i].disqualification() {
+                // the snake is gone, the connection can go as well
+                Some(reason) => {
+                    log(format!("player {} {reason}", i + 1));
+                    if let Some(stream) = host.clients[i].take() {
+                        stream.shutdown(Shutdown::Both).ok();
+                    }
+                }
+                None => log(format!("player {} died", i + 1)),
+            }
+        }
+    }
+    log("game over".to_owned());
+
+    Ok(game)
+}
+
+// the snakes are not on the board between steps
+fn update<R: SeedableRng + Rng, W: Write>(game: &SnakeGame<R, W>) -> ServerMessage {
+    let tiles = game
+        .board
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, tile)| ((x, y), *tile)))
+        .filter(|(_, tile)| *tile != Tile::Empty)
+        .collect();
+
+    ServerMessage::Update {
+        tick: game.tick,
+        state: game.state,
+        boundary: game.boundary,
+        tiles,
+        snakes: game.snakes.clone(),
+        scoreboard: game.scoreboard(),
+    }
+}
+
+// the board of an update, tiles outside of it are left out
+fn board(width: usize, height: usize, tiles: Vec<((usize, usize), Tile)>) -> Board {
+    let mut board = vec![vec![Tile::Empty; width]; height];
+    for ((x, y), tile) in tiles {
+        if let Some(cell) = board.get_mut(y).and_then(|row| row.get_mut(x)) {
+            *cell = tile;
+        }
+    }
+
+    board
+}
+
+impl<R: SeedableRng + Rng, W: Write> SnakeGame<R, W> {
+    // lets clients watch the game while it is played
+    pub fn set_spectators(&mut self, spectators: Spectators) {
+        self.spectators = Some(spectators);
+    }
+
+    // sends the game as it is now to the spectators, if there are any
+    pub(crate) fn update_spectators(&mut self) {
+        if let Some(mut spectators) = self.spectators.take() {
+            spectators.send(&update(self));
+            self.spectators = Some(spectators);
+        }
+    }
+}
+
+// a client the server let in
+struct Client {
+    stream: TcpStream,
+    messages: Receiver<ServerMessage>,
+    // the snake of a player, None for a spectator
+    you: Option<usize>,
+    width: usize,
+    height: usize,
+}
+
+// connects to a server to play or to watch
+fn connect(address: &str, watch: bool) -> io::Result<Client> {
+    let mut stream = TcpStream::connect(address)?;
+    stream.set_nodelay(true)?;
+    let hello = if watch {
+        ClientMessage::Watch
+    } else {
+        ClientMessage::Join
+    };
+    send(&mut stream, &hello)?;
+
+    let messages = receive(stream.try_clone()?);
+    match messages.recv() {
+        Ok(ServerMessage::Welcome { you, width, height }) => {
+            check_board_size(width, height)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            Ok(Client {
+                stream,
+                messages,
+                you,
+                width,
+                height,
+            })
+        }
+        Ok(ServerMessage::Refused { reason }) => {
+            Err(io::Error::new(io::ErrorKind::ConnectionRefused, reason))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            "the server did not let us in",
+        )),
+    }
+}
+
+// plays in a game hosted with `serve`, or only watches it or any other
+// game with spectators: the keys of a player are sent to the server and
+// every step it sends back is drawn to `out`; q leaves the game
+pub fn join<W: Write>(address: &str, out: W, watch: bool, keys: KeyBindings) -> Result<()> {
+    let mut client = connect(address, watch)?;
+    let mut game = SnakeGame::<GameRng, W>::with_seed(client.width, client.height, out, 0);
+    game.set_key_bindings(keys);
+    game.set_up_screen()?;
+    let result = play_remote(&mut game, &client.messages, &mut client.stream, client.you);
+    game.tear_down_screen()?;
+
+    result
+}
+
+// the game only holds what the server sent last, it is never stepped
+fn play_remote<W: Write>(
+    game: &mut SnakeGame<GameRng, W>,
+    messages: &Receiver<ServerMessage>,
+    stream: &mut TcpStream,
+    you: Option<usize>,
+) -> Result<()> {
+    let keyboard = game.keys.keyboard(0, 1);
+    let mut status = match you {
+        Some(_) => "Waiting for the other players...",
+        None => "Waiting for the game...",
+    }
+    .to_owned();
+    let mut scoreboard = String::new();
+    let mut changed = true;
+
+    loop {
+        // everything that arrived since the last frame, the last update wins
+        let over = loop {
+            match messages.try_recv() {
+                Ok(ServerMessage::Lobby { joined, players }) => {
+                    status =
+                        format!("Waiting for the other players, {joined} of {players} joined...");
+                }
+                Ok(ServerMessage::Update {
+                    tick,
+                    state,
+                    boundary,
+                    tiles,
+                    snakes,
+                    scoreboard: text,
+                }) => {
+                    game.board = board(game.board_width, game.board_height, tiles);
+                    game.boundary = boundary;
+                    game.snakes = snakes;
+                    game.tick = tick;
+                    game.state = state;
+                    scoreboard = text;
+                    status = format!("tick {tick}");
+                }
+                Ok(ServerMessage::Welcome { .. } | ServerMessage::Refused { .. }) => {}
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => {
+                    if game.state == GameState::Running {
+                        status =
+                            "The connection to the server was lost. Press any key...".to_owned();
+                    }
+                    break true;
+                }
+            }
+            changed = true;
+        };
+
+        if changed || over {
+            let state = match game.state {
+                GameState::Running => "",
+                GameState::Won => "\n\rThe board is full, you won! Press any key...",
+                GameState::Lost => "\n\rGame over! Press any key...",
+            };
+            game.add_snake_to_board();
+            let controls = match you {
+                Some(you) => format!(
+                    "You are snake {}, steer with {}",
+                    you + 1,
+                    game.keys.steering()
+                ),
+                None => "Watching".to_owned(),
+            };
+            game.draw(&format!("{controls}\n\r{status}{scoreboard}{state}"))?;
+            game.remove_snake_from_board();
+            changed = false;
+        }
+        if over {
+            while !matches!(event::read()?, Event::Key(_)) {}
+            return Ok(());
+        }
+
+        if event::poll(Duration::from_millis(10))? {
+            if let Event::Key(KeyEvent { code, .. }) = event::read()? {
+                if game.keys.action(code) == Some(Action::Quit) {
+                    return Ok(());
+                }
+                if let (Some(_), Some(direction)) = (you, keyboard.direction(code)) {
+                    // a lost connection shows up as the server going away
+                    send(stream, &ClientMessage::Turn { direction }).ok();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{controller::ScriptedController, hazard::Hazard, FoodType, PowerUp};
+
+    const TICKS: u64 = 30;
+
+    // the updates of the first ticks as JSON, to compare them
+    fn updates(client: &Client) -> Vec<serde_json::Value> {
+        let mut updates = Vec::new();
+        while updates.len() <= TICKS as usize {
+            let message = client
+                .messages
+                .recv_timeout(Duration::from_secs(5))
+                .expect("an update from the server");
+            if let ServerMessage::Update { tick, .. } = message {
+                assert_eq!(tick, updates.len() as u64);
+                updates.push(serde_json::to_value(&message).unwrap());
+            }
+        }
+
+        updates
+    }
+
+    #[test]
+    fn updates_carry_everything_on_the_board() {
+        let mut game: SnakeGame<GameRng, _> = SnakeGame::with_seed(12, 8, io::sink(), 1);
+        game.set_boundary(Boundary::Walls);
+        game.add_obstacles(&[(1, 1), (2, 1)]);
+        game.add_portals(&[[(4, 1), (9, 6)]]);
+        game.add_hazards(&[Hazard::bounce((6, 2), (1, 1))]);
+        game.add_snake(
+            Snake::spawn(0, 1, 12, 8),
+            Box::new(ScriptedController::new([])),
+        );
+        game.board[6][1] = Tile::Food(FoodType::PowerUp(PowerUp::Magnet));
+        game.spawn_food();
+
+        let message = serde_json::to_string(&update(&game)).unwrap();
+        let Ok(ServerMessage::Update {
+            boundary, tiles, ..
+        }) = serde_json::from_str(&message)
+        else {
+            panic!("not an update: {message}");
+        };
+        assert_eq!(boundary, Boundary::Walls);
+        assert_eq!(board(12, 8, tiles), game.board);
+    }
+
+    #[test]
+    fn players_of_a_hosted_game_get_the_same_updates() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        // the game itself cannot be sent back, its controllers stay
+        let server = thread::spawn(move || {
+            let game = serve(listener, 2, (20, 12), Duration::from_millis(10), |_| {})?;
+            let alive: Vec<_> = game.snakes.iter().map(|snake| snake.alive).collect();
+            io::Result::Ok((game.state, alive))
+        });
+
+        // the game starts once the second player joined
+        let mut first = connect(&address, false).unwrap();
+        let spectator = connect(&address, true).unwrap();
+        let second = connect(&address, false).unwrap();
+        assert_eq!((first.you, first.width, first.height), (Some(0), 20, 12));
+        assert_eq!((second.you, second.width, second.height), (Some(1), 20, 12));
+        assert_eq!(spectator.you, None);
+
+        let turn = ClientMessage::Turn {
+            direction: Direction::Down,
+        };
+        send(&mut first.stream, &turn).unwrap();
+        let updates = [updates(&first), updates(&second), updates(&spectator)];
+        assert_eq!(updates[0], updates[1]);
+        assert_eq!(updates[0], updates[2]);
+        // the turn of the first player reached the server
+        assert!(updates[0]
+            .iter()
+            .any(|update| update["snakes"][0]["body"][0]["snake_tile_type"]["Head"] == "down"));
+
+        // players leaving lose their snakes, which ends the game
+        drop((first, second));
+        let (state, alive) = server.join().unwrap().unwrap();
+        assert_eq!(state, GameState::Lost);
+        assert_eq!(alive, [false, false]);
+    }
+}
@@ -0,0 +1,215 @@
+// pyo3 generates conversions of `PyErr` into itself for methods returning `PyResult`
+#![allow(clippy::useless_conversion)]
+
+use crate::{
+    check_board_size,
+    env::{EnvConfig, ObservationKind, Rewards, SnakeEnv},
+    Direction, GameState, BOARD_HEIGHT, BOARD_WIDTH,
+};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyDict};
+
+// Python module `snake`, built with the `python` feature:
+//
+//     import snake
+//     game = snake.Game(width=10, height=10, seed=1, observation="features")
+//     observation, reward, done, info = game.step("up")
+//
+// actions are "up", "right", "down", "left" or 0 to 3 in the same order
+#[pymodule]
+fn snake(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Game>()?;
+
+    Ok(())
+}
+
+// headless game with a single snake, see `SnakeEnv`
+#[pyclass(unsendable)]
+struct Game {
+    env: SnakeEnv,
+    done: bool,
+}
+
+#[pymethods]
+impl Game {
+    #[new]
+    #[pyo3(signature = (
+        width = BOARD_WIDTH,
+        height = BOARD_HEIGHT,
+        seed = 0,
+        observation = "grid",
+        food_reward = 1.0,
+        death_reward = -1.0,
+        step_reward = -0.01,
+        max_steps = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        width: usize,
+        height: usize,
+        seed: u64,
+        observation: &str,
+        food_reward: f32,
+        death_reward: f32,
+        step_reward: f32,
+        max_steps: Option<u64>,
+    ) -> PyResult<Self> {
+        check_board_size(width, height).map_err(PyValueError::new_err)?;
+        let observation = match observation {
+            "grid" => ObservationKind::Grid,
+            "features" => ObservationKind::Features,
+            _ => {
+                return Err(PyValueError::new_err(
+                    "observation must be \"grid\" or \"features\"",
+                ))
+            }
+        };
+        let mut env = SnakeEnv::new(EnvConfig {
+            width,
+            height,
+            rewards: Rewards {
+                food: food_reward,
+                death: death_reward,
+                step: step_reward,
+            },
+            observation,
+            max_steps,
+        });
+        env.reset(seed);
+
+        Ok(Self { env, done: false })
+    }
+
+    // starts a new game and returns the first observation
+    fn reset(&mut self, seed: u64) -> Vec<f32> {
+        self.done = false;
+
+        self.env.reset(seed).data
+    }
+
+    // returns (observation, reward, done, info)
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        action: &Bound<'py, PyAny>,
+    ) -> PyResult<(Vec<f32>, f32, bool, Bound<'py, PyDict>)> {
+        let direction = parse_action(action)?;
+        let (observation, reward, done, info) = self.env.step(direction);
+        self.done = done;
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("score", info.score)?;
+        dict.set_item("length", info.length)?;
+        dict.set_item("tick", info.tick)?;
+        dict.set_item("won", info.won)?;
+        dict.set_item("truncated", info.truncated)?;
+
+        Ok((observation.data, reward, done, dict))
+    }
+
+    // flat observation, see `observation_shape`
+    fn observation(&self) -> Vec<f32> {
+        self.env.observe().data
+    }
+
+    #[getter]
+    fn observation_shape(&self) -> Vec<usize> {
+        self.env.observe().shape
+    }
+
+    // rows of tiles: 0 empty, 1 food, 2 snake head, 3 rest of the snake
+    fn board(&self) -> Vec<Vec<u8>> {
+        let game = self.env.game();
+        let mut board: Vec<Vec<u8>> = game
+            .board()
+            .iter()
+            .map(|row| row.iter().map(|tile| u8::from(tile.has_food())).collect())
+            .collect();
+        let snake = &game.snakes()[0];
+        if snake.is_alive() {
+            for (i, tile) in snake.whole_snake().iter().enumerate() {
+                let (x, y) = tile.position();
+                board[y][x] = if i == 0 { 2 } else { 3 };
+            }
+        }
+
+        board
+    }
+
+    // (x, y) of every snake tile, head first
+    fn snake(&self) -> Vec<(usize, usize)> {
+        self.env.game().snakes()[0]
+            .whole_snake()
+            .iter()
+            .map(|tile| tile.position())
+            .collect()
+    }
+
+    #[getter]
+    fn direction(&self) -> &'static str {
+        match self.env.game().snakes()[0].direction() {
+            Direction::Up => "up",
+            Direction::Right => "right",
+            Direction::Down => "down",
+            Direction::Left => "left",
+        }
+    }
+
+    #[getter]
+    fn score(&self) -> u32 {
+        self.env.game().snakes()[0].score()
+    }
+
+    #[getter]
+    fn length(&self) -> usize {
+        self.env.game().snakes()[0].len()
+    }
+
+    #[getter]
+    fn tick(&self) -> u64 {
+        self.env.game().tick()
+    }
+
+    #[getter]
+    fn done(&self) -> bool {
+        self.done
+    }
+
+    #[getter]
+    fn won(&self) -> bool {
+        self.env.game().state() == GameState::Won
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.env.game().board()[0].len()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.env.game().board().len()
+    }
+}
+
+fn parse_action(action: &Bound<'_, PyAny>) -> PyResult<Direction> {
+    let direction = if let Ok(index) = action.extract::<u8>() {
+        match index {
+            0 => Some(Direction::Up),
+            1 => Some(Direction::Right),
+            2 => Some(Direction::Down),
+            3 => Some(Direction::Left),
+            _ => None,
+        }
+    } else {
+        match action.extract::<&str>()? {
+            "up" => Some(Direction::Up),
+            "right" => Some(Direction::Right),
+            "down" => Some(Direction::Down),
+            "left" => Some(Direction::Left),
+            _ => None,
+        }
+    };
+
+    direction.ok_or_else(|| {
+        PyValueError::new_err("action must be \"up\", \"right\", \"down\", \"left\" or 0 to 3")
+    })
+}
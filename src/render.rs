@@ -0,0 +1,213 @@
+use crate::{replay::Replay, theme::Theme, FoodType, GameState, SnakeGame, Tile};
+use rand::{Rng, SeedableRng};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::Path,
+    time::Duration,
+};
+
+// the board as pixels, a square of `tile_size` pixels for every tile;
+// pixels are indices into the colours of `palette`
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+// background, food, obstacles, portals, hazards, power-ups and then head
+// and body of each snake
+const BACKGROUND: u8 = 0;
+const FOOD: u8 = 1;
+const OBSTACLE: u8 = 2;
+const PORTAL: u8 = 3;
+const HAZARD: u8 = 4;
+const POWER_UP: u8 = 5;
+const SNAKES: u8 = 6;
+// a palette has 256 colours, snakes past the ones that fit share them
+const SNAKE_COLOURS: usize = (256 - SNAKES as usize) / 2;
+
+// the colours of the theme in the order used by `Frame`,
+// red, green and blue one after another
+pub fn palette(theme: &Theme, snakes: usize) -> Vec<u8> {
+    let mut palette = Vec::new();
+    palette.extend(theme.background);
+    palette.extend(theme.food);
+    palette.extend(theme.obstacle);
+    palette.extend(theme.portal);
+    palette.extend(theme.hazard);
+    palette.extend(theme.power_up);
+    for i in 0..snakes.min(SNAKE_COLOURS) {
+        let (head, body) = theme.snake(i);
+        palette.extend(head);
+        palette.extend(body);
+    }
+
+    palette
+}
+
+pub fn render<R: SeedableRng + Rng, W: io::Write>(
+    game: &SnakeGame<R, W>,
+    tile_size: usize,
+) -> Frame {
+    let width = game.board_width * tile_size;
+    let height = game.board_height * tile_size;
+    let mut pixels = vec![BACKGROUND; width * height];
+    let mut fill = |(x, y): (usize, usize), inset: usize, color: u8| {
+        for row in y * tile_size + inset..(y + 1) * tile_size - inset {
+            pixels[row * width + x * tile_size + inset..row * width + (x + 1) * tile_size - inset]
+                .fill(color);
+        }
+    };
+
+    for (y, row) in game.board.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if *tile == Tile::Food(FoodType::Blob) {
+                // a bit smaller than a snake tile
+                fill((x, y), tile_size / 4, FOOD);
+            } else if tile.has_food() {
+                fill((x, y), tile_size / 8, POWER_UP);
+            } else if *tile == Tile::Obstacle {
+                fill((x, y), 0, OBSTACLE);
+            } else if *tile == Tile::Portal {
+                fill((x, y), tile_size / 8, PORTAL);
+            } else if *tile == Tile::Hazard {
+                fill((x, y), tile_size / 8, HAZARD);
+            }
+        }
+    }
+    for (i, snake) in game.snakes.iter().enumerate() {
+        if !snake.alive {
+            continue;
+        }
+        // below SNAKE_COLOURS, so the colours stay within the palette
+        let head = SNAKES + 2 * (i % SNAKE_COLOURS) as u8;
+        let body = head + 1;
+        for (j, tile) in snake.whole_snake().iter().enumerate() {
+            fill(tile.position(), 0, if j == 0 { head } else { body });
+        }
+    }
+
+    Frame {
+        width,
+        height,
+        pixels,
+    }
+}
+
+// width or height of the images in pixels; GIF images cannot be larger
+// than 65535 pixels either way, PNG frames are kept to the same
+fn image_size(tiles: usize, tile_size: usize) -> io::Result<u16> {
+    tiles
+        .checked_mul(tile_size)
+        .and_then(|pixels| u16::try_from(pixels).ok())
+        .filter(|&pixels| pixels > 0)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{tiles} tiles of {tile_size} pixels do not fit into an image, \
+                     which is 1 to {} pixels wide and high",
+                    u16::MAX
+                ),
+            )
+        })
+}
+
+// renders every step of the replay into an animated GIF, each frame
+// shown as long as the step after it took, slow-motion included
+pub fn export_gif(replay: &Replay, path: &Path, theme: &Theme, tile_size: usize) -> io::Result<()> {
+    let width = image_size(replay.width, tile_size)?;
+    let height = image_size(replay.height, tile_size)?;
+    let palette = palette(theme, replay.snakes.len());
+    let mut encoder =
+        gif::Encoder::new(BufWriter::new(File::create(path)?), width, height, &palette)
+            .map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+
+    for_each_frame(replay, tile_size, |frame, step_length| {
+        let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, frame.pixels, None);
+        // in hundredths of a second
+        gif_frame.delay = (step_length.as_millis() / 10).clamp(2, u16::MAX.into()) as u16;
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)
+    })
+}
+
+// renders every step of the replay into a PNG file in the directory,
+// numbered from 00000.png; returns the number of frames
+pub fn export_png(
+    replay: &Replay,
+    dir: &Path,
+    theme: &Theme,
+    tile_size: usize,
+) -> io::Result<usize> {
+    let width = image_size(replay.width, tile_size)?;
+    let height = image_size(replay.height, tile_size)?;
+    fs::create_dir_all(dir)?;
+    let palette = palette(theme, replay.snakes.len());
+    let mut count = 0;
+
+    for_each_frame(replay, tile_size, |frame, _| {
+        let file = BufWriter::new(File::create(dir.join(format!("{count:05}.png")))?);
+        let mut encoder = png::Encoder::new(file, width.into(), height.into());
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(palette.clone());
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&frame.pixels))
+            .map_err(io::Error::other)?;
+        count += 1;
+
+        Ok(())
+    })?;
+
+    Ok(count)
+}
+
+// calls f with every frame and how long the step after it takes
+fn for_each_frame(
+    replay: &Replay,
+    tile_size: usize,
+    mut f: impl FnMut(Frame, Duration) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut game = replay.game(io::sink());
+    loop {
+        f(render(&game, tile_size), game.current_step_length())?;
+        if game.state != GameState::Running || game.tick >= replay.len() as u64 {
+            return Ok(());
+        }
+        game.step();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{controller::ScriptedController, GameRng, Snake};
+
+    #[test]
+    fn many_snakes_share_the_palette() {
+        let snakes = 2 * SNAKE_COLOURS + 3;
+        let mut game = SnakeGame::<GameRng, _>::with_seed(60, 60, io::sink(), 1);
+        for i in 0..snakes {
+            let (x, y) = (3 * (i % 20), 2 * (i / 20));
+            let cells = [(x + 2, y), (x + 1, y), (x, y)];
+            game.add_snake(
+                Snake::from_cells(&cells, 60, 60),
+                Box::new(ScriptedController::new([])),
+            );
+        }
+
+        let palette = palette(&Theme::default(), snakes);
+        assert_eq!(palette.len(), 256 * 3);
+        let frame = render(&game, 2);
+        let colours = palette.len() / 3;
+        assert!(frame
+            .pixels
+            .iter()
+            .all(|&pixel| usize::from(pixel) < colours));
+    }
+}
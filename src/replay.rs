@@ -0,0 +1,207 @@
+use crate::{
+    check_board_size,
+    controller::{Controller, GameView},
+    hazard::Hazard,
+    Boundary, Direction, GameRng, Portal, Rules, Snake, SnakeGame, RULES_VERSION,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// everything needed to play a game again: food only depends on the seed,
+// so the starting snakes and the direction of every snake in every step
+// are enough to reconstruct the rest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    // games recorded with other rules would play out differently
+    pub version: u32,
+    pub seed: u64,
+    pub width: usize,
+    pub height: usize,
+    // milliseconds, only matters for watching the game
+    pub step_length: u64,
+    // replays from before there were walls all wrap around
+    #[serde(default)]
+    pub boundary: Boundary,
+    #[serde(default)]
+    pub rules: Rules,
+    #[serde(default)]
+    pub obstacles: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub portals: Vec<Portal>,
+    // where the hazards start
+    #[serde(default)]
+    pub hazards: Vec<Hazard>,
+    // the length to reach to win, if any
+    #[serde(default)]
+    pub target: Option<usize>,
+    #[serde(default)]
+    pub power_ups: bool,
+    // cells of each snake at the start, head first
+    pub snakes: Vec<Vec<(usize, usize)>>,
+    // one string per snake with a character for every step it was alive:
+    // u, r, d or l for the direction it moved in, x when its controller
+    // was disqualified
+    pub inputs: Vec<String>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, width: usize, height: usize, step_length: Duration) -> Self {
+        Self {
+            version: RULES_VERSION,
+            seed,
+            width,
+            height,
+            step_length: step_length.as_millis() as u64,
+            boundary: Boundary::default(),
+            rules: Rules::default(),
+            obstacles: Vec::new(),
+            portals: Vec::new(),
+            hazards: Vec::new(),
+            target: None,
+            power_ups: false,
+            snakes: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let replay: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if replay.version != RULES_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the replay was recorded with rules version {}, this game has version {}",
+                    replay.version, RULES_VERSION
+                ),
+            ));
+        }
+        check_board_size(replay.width, replay.height)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        Ok(replay)
+    }
+
+    // writes the replay into the directory, named after the current time;
+    // returns the path of the file
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = dir.join(format!("snake-{time}-{:x}.json", self.seed));
+        fs::write(&path, serde_json::to_string(self)?)?;
+
+        Ok(path)
+    }
+
+    // number of steps the recorded game lasted
+    pub fn len(&self) -> usize {
+        self.inputs.iter().map(String::len).max().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // the input of the snake in the step that led to the tick,
+    // nothing at the start or once the snake is dead
+    pub fn input(&self, snake: usize, tick: u64) -> Option<char> {
+        let step = usize::try_from(tick).ok()?.checked_sub(1)?;
+
+        self.inputs[snake].get(step..)?.chars().next()
+    }
+
+    // the game as it was at the start, stepping it plays the recording
+    pub fn game<W: io::Write>(&self, out: W) -> SnakeGame<GameRng, W> {
+        let mut game = SnakeGame::with_seed(self.width, self.height, out, self.seed);
+        game.set_step_length(Duration::from_millis(self.step_length));
+        game.set_boundary(self.boundary);
+        game.set_rules(self.rules);
+        game.set_target(self.target);
+        game.set_power_ups(self.power_ups);
+        game.add_obstacles(&self.obstacles.clone());
+        game.add_portals(&self.portals.clone());
+        game.add_hazards(&self.hazards.clone());
+        for (cells, inputs) in self.snakes.iter().zip(&self.inputs) {
+            game.add_snake(
+                Snake::from_cells(cells, self.width, self.height),
+                Box::new(ReplayController::new(inputs)),
+            );
+        }
+        game.spawn_food();
+
+        game
+    }
+
+    pub(crate) fn add_snake(&mut self, snake: &Snake) {
+        self.snakes.push(
+            snake
+                .whole_snake()
+                .iter()
+                .map(|tile| tile.position())
+                .collect(),
+        );
+        self.inputs.push(String::new());
+    }
+
+    // called after the controller of the snake decided
+    pub(crate) fn record(&mut self, index: usize, snake: &Snake) {
+        let input = if snake.alive {
+            match snake.direction() {
+                Direction::Up => 'u',
+                Direction::Right => 'r',
+                Direction::Down => 'd',
+                Direction::Left => 'l',
+            }
+        } else {
+            'x'
+        };
+        self.inputs[index].push(input);
+    }
+}
+
+// moves a snake the way it moved in the recording; snakes stay dead, so a
+// snake that is alive in step n has its input at n - 1, which makes it
+// possible to jump around in the game with `SnakeGame::restore`
+pub struct ReplayController {
+    inputs: Vec<u8>,
+    disqualification: Option<String>,
+}
+
+impl ReplayController {
+    pub fn new(inputs: &str) -> Self {
+        Self {
+            inputs: inputs.bytes().collect(),
+            disqualification: None,
+        }
+    }
+}
+
+impl Controller for ReplayController {
+    fn next_direction(&mut self, view: &GameView) -> Direction {
+        let input = self.inputs.get(view.tick as usize - 1).copied();
+        self.disqualification = None;
+        match input {
+            Some(b'u') => Direction::Up,
+            Some(b'r') => Direction::Right,
+            Some(b'd') => Direction::Down,
+            Some(b'l') => Direction::Left,
+            Some(b'x') => {
+                self.disqualification = Some("disqualified in the recording".to_owned());
+                view.snake().direction()
+            }
+            // the recording stopped before the game was over
+            _ => view.snake().direction(),
+        }
+    }
+
+    fn disqualification(&self) -> Option<&str> {
+        self.disqualification.as_deref()
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{check_board_size, controller::Controller, SnakeGame, Snapshot, RULES_VERSION};
+use rand::{Rng, SeedableRng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader},
+    path::Path,
+    time::Duration,
+};
+
+// a game in progress written to a file to be continued later, with
+// everything needed to carry on exactly where it stopped
+#[derive(Serialize, Deserialize)]
+pub struct SavedGame<R> {
+    // saves made with other rules would play on differently
+    pub version: u32,
+    // how each snake is controlled, controllers are created
+    // again from these when the game is resumed
+    pub controllers: Vec<String>,
+    // milliseconds
+    pub step_length: u64,
+    // saves from before games had an origin were all free games
+    #[serde(default)]
+    pub origin: Origin,
+    pub game: Snapshot<R>,
+}
+
+// what a saved game was started as, so that finishing it after resuming
+// counts the same way as finishing it in one go
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    #[default]
+    Free,
+    // the daily challenge of the date, like 2024-05-31
+    Daily(String),
+    // the campaign level, an index into `LEVELS`
+    Level(usize),
+}
+
+impl<R: SeedableRng + Rng + Clone> SavedGame<R> {
+    pub fn new<W: io::Write>(
+        game: &SnakeGame<R, W>,
+        controllers: Vec<String>,
+        origin: Origin,
+    ) -> Self {
+        Self {
+            version: RULES_VERSION,
+            controllers,
+            step_length: game.step_length.as_millis() as u64,
+            origin,
+            game: game.snapshot(),
+        }
+    }
+
+    // the game as it was saved, driven by the controllers, one per snake
+    pub fn resume<W: io::Write>(
+        &self,
+        out: W,
+        controllers: Vec<Box<dyn Controller>>,
+    ) -> io::Result<SnakeGame<R, W>> {
+        let snapshot = &self.game;
+        if controllers.len() != snapshot.snakes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the saved game has {} snakes, but {} controllers were given",
+                    snapshot.snakes.len(),
+                    controllers.len()
+                ),
+            ));
+        }
+
+        let (width, height) = (snapshot.board[0].len(), snapshot.board.len());
+        let mut game = SnakeGame::with_seed(width, height, out, snapshot.replay.seed);
+        game.restore(snapshot);
+        game.controllers = controllers;
+        game.set_step_length(Duration::from_millis(self.step_length));
+
+        Ok(game)
+    }
+}
+
+impl<R> SavedGame<R> {
+    // daily challenges and campaign levels are resumed only once, their save
+    // is removed as soon as the game is going again
+    pub fn use_up(&self, path: &Path) -> io::Result<()> {
+        match self.origin {
+            Origin::Free => Ok(()),
+            Origin::Daily(_) | Origin::Level(_) => fs::remove_file(path),
+        }
+    }
+}
+
+impl<R: Serialize + DeserializeOwned> SavedGame<R> {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let saved: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if saved.version != RULES_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the game was saved with rules version {}, this game has version {}",
+                    saved.version, RULES_VERSION
+                ),
+            ));
+        }
+        let board = &saved.game.board;
+        check_board_size(board.first().map_or(0, Vec::len), board.len())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        Ok(saved)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{controller::ScriptedController, GameRng, Snake};
+
+    fn resume(path: &Path) -> io::Result<SnakeGame<GameRng, io::Sink>> {
+        let saved: SavedGame<GameRng> = SavedGame::load(path)?;
+        let game = saved.resume(io::sink(), vec![Box::new(ScriptedController::new([]))])?;
+        saved.use_up(path)?;
+
+        Ok(game)
+    }
+
+    #[test]
+    fn daily_saves_are_resumed_only_once() {
+        let path = std::env::temp_dir().join(format!("snake-save-{}.json", std::process::id()));
+        let mut game = SnakeGame::<GameRng, _>::with_seed(10, 10, io::sink(), 1);
+        game.add_snake(
+            Snake::spawn(0, 1, 10, 10),
+            Box::new(ScriptedController::new([])),
+        );
+        game.spawn_food();
+        game.step();
+        let origin = Origin::Daily("2024-05-31".to_owned());
+        SavedGame::new(&game, vec!["keyboard".to_owned()], origin)
+            .save(&path)
+            .unwrap();
+
+        assert_eq!(resume(&path).unwrap().tick(), 1);
+        let err = resume(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
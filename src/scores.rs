@@ -0,0 +1,97 @@
+use crate::{Boundary, Rules};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// entries kept in the table
+const MAX_ENTRIES: usize = 10;
+
+// the best single player games of each rule set, kept in a JSON file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    // best first
+    pub tables: BTreeMap<Rules, Vec<HighScore>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u32,
+    pub length: usize,
+    pub width: usize,
+    pub height: usize,
+    pub boundary: Boundary,
+    // like 2024-05-31
+    pub date: String,
+}
+
+impl HighScores {
+    // an empty table when there is no file yet
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn table(&self, rules: Rules) -> &[HighScore] {
+        self.tables.get(&rules).map_or(&[], Vec::as_slice)
+    }
+
+    // returns the place in the table of the rules, starting at 0, or None
+    // if the score is not good enough; older entries win ties
+    pub fn add(&mut self, rules: Rules, entry: HighScore) -> Option<usize> {
+        let entries = self.tables.entry(rules).or_default();
+        let place = entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(entries.len());
+        if place >= MAX_ENTRIES {
+            return None;
+        }
+        entries.insert(place, entry);
+        entries.truncate(MAX_ENTRIES);
+
+        Some(place)
+    }
+}
+
+// the current date in UTC, like 2024-05-31
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (year, month, day) = civil_date(secs / 86_400);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// year, month and day of the day counted from 1970-01-01, see
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -0,0 +1,244 @@
+use crate::{
+    controller::{Controller, GameView},
+    Boundary, Direction, Snake,
+};
+
+// plays perfectly by following a Hamiltonian cycle, a closed path that
+// visits every tile of the board exactly once; as long as the snake stays
+// on the cycle its body is always behind the head, so it can never crash
+// and eventually fills the whole board
+pub struct HamiltonianSolver {
+    width: usize,
+    height: usize,
+    // tiles in the order they are visited by the cycle
+    cycle: Vec<(usize, usize)>,
+    // position of every tile in the cycle, indexed by [y][x]
+    order: Vec<Vec<usize>>,
+}
+
+impl HamiltonianSolver {
+    // returns None if no cycle can be built for the given board; with walls
+    // that is the case for an odd number of tiles, like on a chessboard
+    // coloured in two colours a cycle visits as many tiles of each colour
+    pub fn new(width: usize, height: usize, boundary: Boundary) -> Option<Self> {
+        let cycle = build_cycle(width, height, boundary)?;
+        let mut order = vec![vec![0; width]; height];
+        for (i, &(x, y)) in cycle.iter().enumerate() {
+            order[y][x] = i;
+        }
+
+        Some(Self {
+            width,
+            height,
+            cycle,
+            order,
+        })
+    }
+
+    // snake of the given length lying on the start of the cycle
+    pub fn initial_snake(&self, length: usize) -> Snake {
+        let cells: Vec<_> = self.cycle[..length].iter().rev().copied().collect();
+
+        Snake::from_cells(&cells, self.width, self.height)
+    }
+}
+
+impl Controller for HamiltonianSolver {
+    // shortcuts that skip part of the cycle are only taken while the snake
+    // is short and only if the head stays far enough ahead of the tail
+    fn next_direction(&mut self, view: &GameView) -> Direction {
+        let snake = view.snake();
+        let len = self.cycle.len();
+        let head = snake.head();
+        let head_pos = (usize::from(head.x), usize::from(head.y));
+        // how far ahead of the head a tile is when following the cycle
+        let distance = |(x, y): (usize, usize)| {
+            (self.order[y][x] + len - self.order[head_pos.1][head_pos.0]) % len
+        };
+
+        let next = self.cycle[(self.order[head_pos.1][head_pos.0] + 1) % len];
+        let mut best = (
+            Direction::between(head_pos, next, self.width, self.height),
+            1,
+        );
+
+        let tail = snake.last();
+        let tail_distance = distance((usize::from(tail.x), usize::from(tail.y)));
+        // every tile that is still being swallowed will make the snake longer,
+        // which keeps the tail in place for one more step
+        let growth = snake
+            .whole_snake()
+            .iter()
+            .filter(|tile| tile.eating)
+            .count();
+        let mut max_shortcut = if snake.len() * 2 < len {
+            tail_distance.saturating_sub(growth + 3)
+        } else {
+            0
+        };
+        // skipping past the nearest food only makes the game longer
+        let food_distance = view
+            .board
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, tile)| (x, y, tile)))
+            .filter(|(_, _, tile)| tile.has_food())
+            .map(|(x, y, _)| distance((x, y)))
+            .min();
+        if let Some(food_distance) = food_distance {
+            max_shortcut = max_shortcut.min(food_distance);
+        }
+
+        for direction in [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ] {
+            let Some(tile) = view.step(head_pos, direction) else {
+                continue;
+            };
+            let tile_distance = distance(tile);
+            if tile_distance > best.1 && tile_distance <= max_shortcut && !view.is_occupied(tile) {
+                best = (direction, tile_distance);
+            }
+        }
+
+        best.0
+    }
+}
+
+// if the height is even, the cycle goes right along the top row, zigzags
+// down through the remaining columns and returns up the first column;
+// if only the width is even, the same is done with the board transposed;
+// if both are odd, the zigzag covers all but the last row, which is then
+// spliced in by wrapping around the board edge, which walls do not allow
+fn build_cycle(width: usize, height: usize, boundary: Boundary) -> Option<Vec<(usize, usize)>> {
+    if width < 3 || height < 3 {
+        return None;
+    }
+
+    if height.is_multiple_of(2) {
+        Some(zigzag(width, height))
+    } else if width.is_multiple_of(2) {
+        Some(
+            zigzag(height, width)
+                .into_iter()
+                .map(|(x, y)| (y, x))
+                .collect(),
+        )
+    } else if boundary == Boundary::Walls {
+        None
+    } else {
+        let mut cycle = zigzag(width, height - 1);
+        // the second to last row is walked right to left, so (2, y) is
+        // directly followed by (1, y)
+        let y = height - 2;
+        let splice_at = cycle.iter().position(|&tile| tile == (2, y)).unwrap() + 1;
+        let last_row = (2..width).chain(0..2).map(|x| (x, height - 1));
+        cycle.splice(splice_at..splice_at, last_row);
+
+        Some(cycle)
+    }
+}
+
+// height must be even
+fn zigzag(width: usize, height: usize) -> Vec<(usize, usize)> {
+    let mut cycle = Vec::with_capacity(width * height);
+    cycle.extend((0..width).map(|x| (x, 0)));
+    for y in 1..height {
+        if y % 2 == 1 {
+            cycle.extend((1..width).rev().map(|x| (x, y)));
+        } else {
+            cycle.extend((1..width).map(|x| (x, y)));
+        }
+    }
+    cycle.extend((1..height).rev().map(|y| (0, y)));
+
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZES: [(usize, usize); 9] = [
+        (4, 4),
+        (6, 8),
+        (4, 5),
+        (5, 4),
+        (6, 7),
+        (3, 3),
+        (5, 5),
+        (7, 9),
+        (3, 8),
+    ];
+
+    // visits every tile once and each tile is followed by a neighbour,
+    // the last one by the first
+    fn assert_hamiltonian(
+        cycle: &[(usize, usize)],
+        width: usize,
+        height: usize,
+        boundary: Boundary,
+    ) {
+        let mut visited = vec![vec![false; width]; height];
+        for &(x, y) in cycle {
+            assert!(
+                !visited[y][x],
+                "({x}, {y}) visited twice on {width}x{height}"
+            );
+            visited[y][x] = true;
+        }
+        assert_eq!(
+            cycle.len(),
+            width * height,
+            "not every tile visited on {width}x{height}"
+        );
+
+        for (i, &tile) in cycle.iter().enumerate() {
+            let next = cycle[(i + 1) % cycle.len()];
+            let adjacent = [
+                Direction::Up,
+                Direction::Right,
+                Direction::Down,
+                Direction::Left,
+            ]
+            .into_iter()
+            .any(|direction| boundary.apply(direction, tile, width, height) == Some(next));
+            assert!(
+                adjacent,
+                "{tile:?} and {next:?} are not neighbours on {width}x{height} with {}",
+                boundary.name()
+            );
+        }
+    }
+
+    #[test]
+    fn cycles_are_hamiltonian_with_wrapping() {
+        for (width, height) in SIZES {
+            let cycle = build_cycle(width, height, Boundary::Wrap).unwrap();
+            assert_hamiltonian(&cycle, width, height, Boundary::Wrap);
+        }
+    }
+
+    #[test]
+    fn cycles_are_hamiltonian_with_walls() {
+        for (width, height) in SIZES {
+            let cycle = build_cycle(width, height, Boundary::Walls);
+            if width % 2 == 1 && height % 2 == 1 {
+                assert_eq!(cycle, None, "{width}x{height}");
+            } else {
+                assert_hamiltonian(&cycle.unwrap(), width, height, Boundary::Walls);
+            }
+        }
+    }
+
+    #[test]
+    fn boards_that_are_too_small_have_no_cycle() {
+        for boundary in Boundary::ALL {
+            assert_eq!(build_cycle(2, 6, boundary), None);
+            assert_eq!(build_cycle(6, 1, boundary), None);
+        }
+    }
+}
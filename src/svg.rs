@@ -0,0 +1,249 @@
+use crate::{
+    replay::Replay,
+    theme::{Color, Theme},
+    BodyPartDirection, Direction, FoodType, GameState, SnakeGame, SnakePart, SnakeTile, Tile,
+};
+use rand::{Rng, SeedableRng};
+use std::{
+    fmt::Write as FmtWrite,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// size of a tile in SVG units
+const TILE: usize = 20;
+
+// the board as an SVG image: a grid, the food and every snake as one
+// continuous line following its corners, broken only where it goes
+// over the edge of the board
+pub fn render<R: SeedableRng + Rng, W: io::Write>(game: &SnakeGame<R, W>, theme: &Theme) -> String {
+    let (width, height) = (game.board_width * TILE, game.board_height * TILE);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">\n\
+         <rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>\n",
+        hex(theme.background)
+    );
+
+    svg.push_str("<g stroke=\"#808080\" stroke-opacity=\"0.25\" stroke-width=\"1\">\n");
+    for x in 1..game.board_width {
+        writeln!(
+            svg,
+            "<line x1=\"{0}\" y1=\"0\" x2=\"{0}\" y2=\"{height}\"/>",
+            x * TILE
+        )
+        .unwrap();
+    }
+    for y in 1..game.board_height {
+        writeln!(
+            svg,
+            "<line x1=\"0\" y1=\"{0}\" x2=\"{width}\" y2=\"{0}\"/>",
+            y * TILE
+        )
+        .unwrap();
+    }
+    svg.push_str("</g>\n");
+
+    for (y, row) in game.board.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if let Tile::Food(food) = tile {
+                // power-ups are bigger
+                let (cx, cy) = center((x, y));
+                let (r, color) = match food {
+                    FoodType::Blob => (TILE / 4, theme.food),
+                    FoodType::PowerUp(_) => (TILE * 2 / 5, theme.power_up),
+                };
+                writeln!(
+                    svg,
+                    "<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{r}\" fill=\"{}\"/>",
+                    hex(color)
+                )
+                .unwrap();
+            } else if *tile == Tile::Obstacle {
+                writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{TILE}\" height=\"{TILE}\" fill=\"{}\"/>",
+                    x * TILE,
+                    y * TILE,
+                    hex(theme.obstacle)
+                )
+                .unwrap();
+            } else if *tile == Tile::Portal {
+                let (cx, cy) = center((x, y));
+                writeln!(
+                    svg,
+                    "<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{}\" fill=\"none\" stroke=\"{}\" \
+                     stroke-width=\"{}\"/>",
+                    TILE * 2 / 5,
+                    hex(theme.portal),
+                    TILE / 8
+                )
+                .unwrap();
+            } else if *tile == Tile::Hazard {
+                // a diamond
+                let (cx, cy) = center((x, y));
+                let r = TILE * 2 / 5;
+                writeln!(
+                    svg,
+                    "<polygon points=\"{cx},{} {},{cy} {cx},{} {},{cy}\" fill=\"{}\"/>",
+                    cy - r,
+                    cx + r,
+                    cy + r,
+                    cx - r,
+                    hex(theme.hazard)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    for (i, snake) in game.snakes.iter().enumerate() {
+        if !snake.alive {
+            continue;
+        }
+        let (head_color, body_color) = theme.snake(i);
+        writeln!(
+            svg,
+            "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" \
+             stroke-linecap=\"round\" stroke-linejoin=\"round\"/>",
+            path(snake.whole_snake()),
+            hex(body_color),
+            TILE * 3 / 5
+        )
+        .unwrap();
+        // food being swallowed shows as a bulge
+        for tile in snake.whole_snake().iter().filter(|tile| tile.is_eating()) {
+            let (cx, cy) = center(tile.position());
+            writeln!(
+                svg,
+                "<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{}\" fill=\"{}\"/>",
+                TILE * 2 / 5,
+                hex(body_color)
+            )
+            .unwrap();
+        }
+        let (cx, cy) = center(snake.head().position());
+        writeln!(
+            svg,
+            "<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{}\" fill=\"{}\"/>",
+            TILE * 2 / 5,
+            hex(head_color)
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// writes the board next to the other files of the game,
+// named after the current time and tick; returns the path
+pub fn save<R: SeedableRng + Rng, W: io::Write>(
+    game: &SnakeGame<R, W>,
+    theme: &Theme,
+    dir: &Path,
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = dir.join(format!("snake-{time}-tick-{}.svg", game.tick));
+    fs::write(&path, render(game, theme))?;
+
+    Ok(path)
+}
+
+// writes the board of the replay at the tick, or at its end
+pub fn export(replay: &Replay, path: &Path, theme: &Theme, tick: Option<u64>) -> io::Result<()> {
+    let tick = tick.unwrap_or(u64::MAX).min(replay.len() as u64);
+    let mut game = replay.game(io::sink());
+    while game.tick < tick && game.state == GameState::Running {
+        game.step();
+    }
+
+    fs::write(path, render(&game, theme))
+}
+
+// path from the head to the tail; every tile adds a piece from the side
+// facing the head to the side facing the tail, bent around the center
+// for corners, so consecutive pieces meet on the shared side
+fn path(body: &[SnakeTile]) -> String {
+    let mut path = String::new();
+    let mut pen = None;
+    for tile in body {
+        let position = tile.position();
+        let (cx, cy) = center(position);
+        let (from, to) = match tile.part() {
+            SnakePart::Head(direction) => (None, Some(direction.opposite())),
+            SnakePart::Body(direction) => {
+                let (toward_head, toward_tail) = sides(direction);
+                (Some(toward_head), Some(toward_tail))
+            }
+            SnakePart::Tail(direction) => (Some(direction), None),
+        };
+
+        let start = from.map_or((cx, cy), |side| side_point(position, side));
+        if pen != Some(start) {
+            write!(path, "M{} {} ", start.0, start.1).unwrap();
+        }
+        let end = match to {
+            Some(side) => side_point(position, side),
+            None => (cx, cy),
+        };
+        if from.is_some() && to.is_some() {
+            write!(path, "Q{cx} {cy} {} {} ", end.0, end.1).unwrap();
+        } else {
+            write!(path, "L{} {} ", end.0, end.1).unwrap();
+        }
+        pen = Some(end);
+    }
+
+    path.trim_end().to_owned()
+}
+
+// the sides of the tile a body part connects: toward the head,
+// which is the direction it is moving in, and toward the tail
+fn sides(direction: BodyPartDirection) -> (Direction, Direction) {
+    use BodyPartDirection::*;
+    use Direction::{Down as D, Left as L, Right as R, Up as U};
+
+    match direction {
+        Up => (U, D),
+        Down => (D, U),
+        Left => (L, R),
+        Right => (R, L),
+        // ┏
+        TopLeftCornerRight => (R, D),
+        TopLeftCornerDown => (D, R),
+        // ┓
+        TopRightCornerLeft => (L, D),
+        TopRightCornerDown => (D, L),
+        // ┗
+        BottomLeftCornerRight => (R, U),
+        BottomLeftCornerUp => (U, R),
+        // ┛
+        BottomRightCornerLeft => (L, U),
+        BottomRightCornerUp => (U, L),
+    }
+}
+
+fn center((x, y): (usize, usize)) -> (usize, usize) {
+    (x * TILE + TILE / 2, y * TILE + TILE / 2)
+}
+
+// middle of one side of the tile
+fn side_point(position: (usize, usize), side: Direction) -> (usize, usize) {
+    let (cx, cy) = center(position);
+    match side {
+        Direction::Up => (cx, cy - TILE / 2),
+        Direction::Down => (cx, cy + TILE / 2),
+        Direction::Left => (cx - TILE / 2, cy),
+        Direction::Right => (cx + TILE / 2, cy),
+    }
+}
+
+fn hex([r, g, b]: Color) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
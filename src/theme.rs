@@ -0,0 +1,77 @@
+// red, green and blue
+pub type Color = [u8; 3];
+
+// colours of everything on the board
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    pub food: Color,
+    pub obstacle: Color,
+    pub portal: Color,
+    pub hazard: Color,
+    pub power_up: Color,
+    // head and body colour of each snake, starting over
+    // at the first one when there are more snakes
+    pub snakes: &'static [(Color, Color)],
+}
+
+pub const THEMES: [Theme; 3] = [
+    Theme {
+        name: "classic",
+        background: [16, 16, 16],
+        food: [220, 50, 47],
+        obstacle: [110, 110, 120],
+        portal: [80, 200, 220],
+        hazard: [255, 140, 0],
+        power_up: [240, 240, 90],
+        snakes: &[
+            ([133, 230, 80], [60, 170, 40]),
+            ([90, 170, 255], [40, 110, 210]),
+            ([250, 210, 70], [200, 150, 20]),
+            ([230, 110, 220], [170, 60, 160]),
+        ],
+    },
+    Theme {
+        name: "light",
+        background: [240, 236, 224],
+        food: [200, 40, 40],
+        obstacle: [120, 100, 80],
+        portal: [40, 140, 170],
+        hazard: [220, 100, 0],
+        power_up: [150, 60, 190],
+        snakes: &[
+            ([30, 110, 30], [70, 150, 60]),
+            ([20, 70, 160], [60, 110, 200]),
+            ([170, 100, 0], [210, 140, 30]),
+            ([120, 30, 120], [160, 70, 160]),
+        ],
+    },
+    Theme {
+        name: "mono",
+        background: [0, 0, 0],
+        food: [170, 170, 170],
+        obstacle: [90, 90, 90],
+        portal: [255, 255, 255],
+        hazard: [130, 130, 130],
+        power_up: [220, 220, 220],
+        snakes: &[([255, 255, 255], [210, 210, 210])],
+    },
+];
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<&'static Theme> {
+        THEMES.iter().find(|theme| theme.name == name)
+    }
+
+    // head and body colour of the snake with the index
+    pub fn snake(&self, index: usize) -> (Color, Color) {
+        self.snakes[index % self.snakes.len()]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0].clone()
+    }
+}
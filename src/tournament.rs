@@ -0,0 +1,172 @@
+use crate::{controller::ControllerKind, GameRng, GameState, Snake, SnakeGame};
+use serde::Serialize;
+use std::{io, time::Duration};
+
+// plays seeded games between bots using the same rules as the normal game,
+// just without drawing or waiting between steps
+pub struct Tournament {
+    pub bots: Vec<ControllerKind>,
+    pub games: u64,
+    pub seed: u64,
+    pub max_ticks: u64,
+    pub width: usize,
+    pub height: usize,
+    pub move_time_limit: Duration,
+}
+
+#[derive(Serialize)]
+pub struct Results {
+    games: u64,
+    seed: u64,
+    max_ticks: u64,
+    width: usize,
+    height: usize,
+    // sorted from best to worst
+    bots: Vec<BotResults>,
+}
+
+#[derive(Serialize, Default)]
+struct BotResults {
+    name: String,
+    wins: u64,
+    win_rate: f64,
+    average_length: f64,
+    average_survival_ticks: f64,
+    timeouts: u64,
+    disqualifications: u64,
+}
+
+impl Tournament {
+    pub fn run(&self) -> io::Result<Results> {
+        let count = self.bots.len();
+        let mut bots: Vec<_> = self
+            .bots
+            .iter()
+            .map(|bot| BotResults {
+                name: bot.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let mut total_length = vec![0; count];
+        let mut total_survival = vec![0; count];
+
+        for game_index in 0..self.games {
+            let mut game: SnakeGame<GameRng, _> = SnakeGame::with_seed(
+                self.width,
+                self.height,
+                io::sink(),
+                self.seed.wrapping_add(game_index),
+            );
+            // bots take turns in every starting position
+            let seats: Vec<_> = (0..count)
+                .map(|seat| (seat + game_index as usize) % count)
+                .collect();
+            for (seat, &bot) in seats.iter().enumerate() {
+                let controller = self.bots[bot].create(self.move_time_limit)?;
+                game.add_snake(
+                    Snake::spawn(seat, count, self.width, self.height),
+                    controller,
+                );
+            }
+
+            game.spawn_food();
+            let mut survival = vec![None; count];
+            while game.state == GameState::Running && game.tick < self.max_ticks {
+                game.step();
+                for (seat, snake) in game.snakes.iter().enumerate() {
+                    if !snake.alive && survival[seat].is_none() {
+                        survival[seat] = Some(game.tick);
+                    }
+                }
+            }
+            let survival: Vec<_> = survival
+                .into_iter()
+                .map(|ticks| ticks.unwrap_or(game.tick))
+                .collect();
+
+            for (seat, &bot) in seats.iter().enumerate() {
+                let controller = &game.controllers[seat];
+                total_length[bot] += game.snakes[seat].len();
+                total_survival[bot] += survival[seat];
+                bots[bot].timeouts += u64::from(controller.timeouts());
+                if controller.disqualification().is_some() {
+                    bots[bot].disqualifications += 1;
+                }
+            }
+            if let Some(seat) = winner(&game, &survival) {
+                bots[seats[seat]].wins += 1;
+            }
+            eprintln!(
+                "game {}/{} finished after {} steps",
+                game_index + 1,
+                self.games,
+                game.tick
+            );
+        }
+
+        for (i, bot) in bots.iter_mut().enumerate() {
+            let games = self.games.max(1) as f64;
+            bot.win_rate = bot.wins as f64 / games;
+            bot.average_length = total_length[i] as f64 / games;
+            bot.average_survival_ticks = total_survival[i] as f64 / games;
+        }
+        bots.sort_by(|a, b| {
+            b.win_rate
+                .total_cmp(&a.win_rate)
+                .then(b.average_length.total_cmp(&a.average_length))
+        });
+
+        Ok(Results {
+            games: self.games,
+            seed: self.seed,
+            max_ticks: self.max_ticks,
+            width: self.width,
+            height: self.height,
+            bots,
+        })
+    }
+}
+
+// the snake that survived the longest, or the longer one if several survived
+// equally long; a lone snake only wins by filling the board
+fn winner<W: io::Write>(game: &SnakeGame<GameRng, W>, survival: &[u64]) -> Option<usize> {
+    if game.snakes.len() == 1 {
+        return (game.state == GameState::Won).then_some(0);
+    }
+
+    let rank = |seat: usize| (survival[seat], game.snakes[seat].len());
+    let best = (0..game.snakes.len()).map(rank).max()?;
+    let mut best_seats = (0..game.snakes.len()).filter(|&seat| rank(seat) == best);
+    match (best_seats.next(), best_seats.next()) {
+        (Some(seat), None) => Some(seat),
+        _ => None,
+    }
+}
+
+pub fn print_leaderboard(results: &Results) {
+    println!(
+        "{} games on a {}x{} board, seeds {}..{}",
+        results.games,
+        results.width,
+        results.height,
+        results.seed,
+        results.seed.wrapping_add(results.games)
+    );
+    println!(
+        "{:>4}  {:<30} {:>6} {:>8} {:>10} {:>10} {:>9} {:>5}",
+        "rank", "bot", "wins", "win rate", "avg length", "avg ticks", "timeouts", "dq"
+    );
+    for (rank, bot) in results.bots.iter().enumerate() {
+        println!(
+            "{:>4}  {:<30} {:>6} {:>7.1}% {:>10.1} {:>10.1} {:>9} {:>5}",
+            rank + 1,
+            bot.name,
+            bot.wins,
+            bot.win_rate * 100.0,
+            bot.average_length,
+            bot.average_survival_ticks,
+            bot.timeouts,
+            bot.disqualifications
+        );
+    }
+}
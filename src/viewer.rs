@@ -0,0 +1,235 @@
+use crate::{
+    config::{Action, Key, KeyBindings},
+    replay::Replay,
+    svg,
+    theme::Theme,
+    GameRng, GameState, SnakeGame, Snapshot,
+};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent},
+    Result,
+};
+use std::{
+    fmt::Write as FmtWrite,
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+// steps between the snapshots going back in the replay starts from
+const SNAPSHOT_INTERVAL: u64 = 100;
+const MAX_SPEED: u32 = 32;
+
+// plays a replay back in the terminal by running the recorded game again
+pub struct Viewer<W: Write> {
+    replay: Replay,
+    game: SnakeGame<GameRng, W>,
+    // snapshots[i] is the game at tick i * SNAPSHOT_INTERVAL,
+    // taken the first time the replay gets there
+    snapshots: Vec<Snapshot<GameRng>>,
+    paused: bool,
+    speed: u32,
+    // digits typed after pressing g, the tick to jump to
+    jump: Option<String>,
+    // shown until the next key, like where the board was saved
+    message: Option<String>,
+}
+
+impl<W: Write> Viewer<W> {
+    // quitting and saving the board use the keys of the game
+    pub fn new(replay: Replay, out: W, keys: KeyBindings) -> Self {
+        let mut game = replay.game(out);
+        game.set_key_bindings(keys);
+        let snapshots = vec![game.snapshot()];
+
+        Self {
+            replay,
+            game,
+            snapshots,
+            paused: false,
+            speed: 1,
+            jump: None,
+            message: None,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.game.set_up_screen()?;
+        let result = self.watch();
+        self.game.tear_down_screen()?;
+
+        result
+    }
+
+    fn watch(&mut self) -> Result<()> {
+        let mut next_step = Instant::now() + self.step_length();
+        loop {
+            self.draw()?;
+
+            // waits for a key, but only until the next step is due
+            let event = if self.paused || self.is_over() {
+                Some(event::read()?)
+            } else {
+                let timeout = next_step.saturating_duration_since(Instant::now());
+                if event::poll(timeout)? {
+                    Some(event::read()?)
+                } else {
+                    None
+                }
+            };
+            match event {
+                Some(Event::Key(KeyEvent { code, .. })) => {
+                    if !self.handle_key(code) {
+                        break;
+                    }
+                }
+                // anything else, like resizing, just redraws
+                Some(_) => {}
+                None => {
+                    self.seek(self.game.tick() + 1);
+                    next_step = Instant::now() + self.step_length();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // returns false to stop watching
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        self.message = None;
+        if let Some(jump) = &mut self.jump {
+            match key {
+                KeyCode::Char(c) if c.is_ascii_digit() => jump.push(c),
+                KeyCode::Backspace => {
+                    jump.pop();
+                }
+                KeyCode::Enter => {
+                    if let Ok(tick) = jump.parse() {
+                        self.seek(tick);
+                    }
+                    self.jump = None;
+                }
+                KeyCode::Esc => self.jump = None,
+                _ => {}
+            }
+            return true;
+        }
+
+        match self.game.keys.action(key) {
+            Some(Action::Quit) => return false,
+            Some(Action::Snapshot) => {
+                self.paused = true;
+                self.message = Some(
+                    match svg::save(&self.game, &Theme::default(), Path::new(".")) {
+                        Ok(path) => format!("board saved to {}", path.display()),
+                        Err(e) => format!("could not save the board: {e}"),
+                    },
+                );
+                return true;
+            }
+            _ => {}
+        }
+
+        let tick = self.game.tick();
+        match key {
+            KeyCode::Esc => return false,
+            KeyCode::Char(' ') => self.paused = !self.paused,
+            KeyCode::Right | KeyCode::Char('.') => {
+                self.paused = true;
+                self.seek(tick + 1);
+            }
+            KeyCode::Left | KeyCode::Char(',') => {
+                self.paused = true;
+                self.seek(tick.saturating_sub(1));
+            }
+            KeyCode::Char('+') | KeyCode::Char('f') => self.speed = (self.speed * 2).min(MAX_SPEED),
+            KeyCode::Char('-') => self.speed = (self.speed / 2).max(1),
+            KeyCode::Home => self.seek(0),
+            KeyCode::End => self.seek(self.replay.len() as u64),
+            KeyCode::Char('g') => self.jump = Some(String::new()),
+            _ => {}
+        }
+
+        true
+    }
+
+    // plays the game up to the tick, going back to the last snapshot
+    // before it first if the tick was already played or is far ahead
+    fn seek(&mut self, tick: u64) {
+        let tick = tick.min(self.replay.len() as u64);
+        let snapshot =
+            &self.snapshots[((tick / SNAPSHOT_INTERVAL) as usize).min(self.snapshots.len() - 1)];
+        if tick < self.game.tick() || snapshot.tick() > self.game.tick() {
+            self.game.restore(snapshot);
+        }
+
+        while self.game.tick() < tick && self.game.state() == GameState::Running {
+            self.game.step();
+            if self.game.tick() == self.snapshots.len() as u64 * SNAPSHOT_INTERVAL {
+                self.snapshots.push(self.game.snapshot());
+            }
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.game.state() != GameState::Running || self.game.tick() >= self.replay.len() as u64
+    }
+
+    fn step_length(&self) -> Duration {
+        self.game.current_step_length() / self.speed
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let tick = self.game.tick();
+        let snapshot = self
+            .game
+            .keys
+            .keys(Action::Snapshot)
+            .first()
+            .map(Key::label);
+        let mut text = format!(
+            "Replay: space pauses, arrows step, +/- change the speed, g jumps to a tick,\n\r\
+             {} saves the board as SVG\n\r\
+             tick {tick}/{}, speed x{}",
+            snapshot.as_deref().unwrap_or("-"),
+            self.replay.len(),
+            self.speed
+        );
+        if self.paused {
+            text.push_str(", paused");
+        }
+        match self.game.state() {
+            GameState::Won => text.push_str(", the board is full"),
+            GameState::Lost => text.push_str(", game over"),
+            GameState::Running if self.is_over() => text.push_str(", end of the recording"),
+            GameState::Running => {}
+        }
+
+        text.push_str("\n\rinputs:");
+        for snake in 0..self.replay.snakes.len() {
+            let input = match self.replay.input(snake, tick) {
+                Some('u') => "up",
+                Some('r') => "right",
+                Some('d') => "down",
+                Some('l') => "left",
+                Some('x') => "disqualified",
+                _ => "-",
+            };
+            write!(&mut text, " snake {} {input}", snake + 1).unwrap();
+        }
+        text.push_str(&self.game.scoreboard());
+        if let Some(jump) = &self.jump {
+            write!(&mut text, "\n\rjump to tick: {jump}_").unwrap();
+        }
+        if let Some(message) = &self.message {
+            write!(&mut text, "\n\r{message}").unwrap();
+        }
+
+        self.game.add_snake_to_board();
+        let result = self.game.draw(&text);
+        self.game.remove_snake_from_board();
+
+        result
+    }
+}
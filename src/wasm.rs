@@ -0,0 +1,133 @@
+use crate::{
+    check_board_size,
+    env::{EnvConfig, SnakeEnv},
+    Direction, GameState,
+};
+use wasm_bindgen::prelude::*;
+
+// JavaScript API, built with the `wasm` feature:
+//
+//     import { Game } from "./pkg/snake.js";
+//     const game = new Game(20, 10, 42);
+//     game.step("up");
+//     game.board(); // Uint8Array of width * height tiles, row by row
+//
+// the game only moves when `step` is called, so the page decides the speed
+#[wasm_bindgen]
+pub struct Game {
+    env: SnakeEnv,
+}
+
+#[wasm_bindgen]
+impl Game {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize, seed: u32) -> Result<Game, JsError> {
+        check_board_size(width, height).map_err(|err| JsError::new(&err))?;
+        let mut env = SnakeEnv::new(EnvConfig {
+            width,
+            height,
+            ..Default::default()
+        });
+        env.reset(u64::from(seed));
+
+        Ok(Self { env })
+    }
+
+    // turns the snake ("up", "right", "down" or "left", reversing is
+    // ignored) or keeps it going without a direction and moves it one tile;
+    // returns whether the game is still running
+    pub fn step(&mut self, direction: Option<String>) -> Result<bool, JsError> {
+        let direction = match direction.as_deref() {
+            None => self.env.game().snakes()[0].direction(),
+            Some("up") => Direction::Up,
+            Some("right") => Direction::Right,
+            Some("down") => Direction::Down,
+            Some("left") => Direction::Left,
+            Some(other) => return Err(JsError::new(&format!("unknown direction {other:?}"))),
+        };
+        let (_, _, done, _) = self.env.step(direction);
+
+        Ok(!done)
+    }
+
+    // tiles row by row: 0 empty, 1 food, 2 snake head, 3 rest of the snake
+    pub fn board(&self) -> Vec<u8> {
+        let game = self.env.game();
+        let width = self.width();
+        let mut board: Vec<u8> = game
+            .board()
+            .iter()
+            .flatten()
+            .map(|tile| u8::from(tile.has_food()))
+            .collect();
+        let snake = &game.snakes()[0];
+        if snake.is_alive() {
+            for (i, tile) in snake.whole_snake().iter().enumerate() {
+                let (x, y) = tile.position();
+                board[y * width + x] = if i == 0 { 2 } else { 3 };
+            }
+        }
+
+        board
+    }
+
+    // x and y of every snake tile one after another, head first
+    pub fn snake(&self) -> Vec<u32> {
+        self.env.game().snakes()[0]
+            .whole_snake()
+            .iter()
+            .flat_map(|tile| {
+                let (x, y) = tile.position();
+                [x as u32, y as u32]
+            })
+            .collect()
+    }
+
+    // "running", "won" or "lost"
+    #[wasm_bindgen(getter)]
+    pub fn state(&self) -> String {
+        match self.env.game().state() {
+            GameState::Running => "running",
+            GameState::Won => "won",
+            GameState::Lost => "lost",
+        }
+        .to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn direction(&self) -> String {
+        match self.env.game().snakes()[0].direction() {
+            Direction::Up => "up",
+            Direction::Right => "right",
+            Direction::Down => "down",
+            Direction::Left => "left",
+        }
+        .to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> u32 {
+        self.env.game().snakes()[0].score()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.env.game().snakes()[0].len()
+    }
+
+    // a JavaScript number rather than a BigInt
+    #[wasm_bindgen(getter)]
+    pub fn tick(&self) -> f64 {
+        self.env.game().tick() as f64
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.env.game().board()[0].len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.env.game().board().len()
+    }
+}
@@ -0,0 +1,61 @@
+// smoke test of the JavaScript API under node, see the README:
+//
+//     cargo build --release --target wasm32-unknown-unknown --no-default-features --features wasm
+//     wasm-bindgen --target nodejs --out-dir wasm/pkg target/wasm32-unknown-unknown/release/snake.wasm
+//     node wasm/test.mjs
+import assert from "node:assert/strict";
+import { createRequire } from "node:module";
+
+const { Game } = createRequire(import.meta.url)("./pkg/snake.js");
+
+const game = new Game(10, 8, 42);
+assert.equal(game.width, 10);
+assert.equal(game.height, 8);
+assert.equal(game.state, "running");
+assert.equal(game.direction, "right");
+assert.equal(game.tick, 0);
+
+const board = game.board();
+assert.equal(board.length, 10 * 8);
+assert.equal(board.filter((tile) => tile === 2).length, 1);
+assert.ok(board.includes(1), "food is spawned");
+
+const snake = game.snake();
+assert.equal(snake.length, game.length * 2);
+const [x, y] = snake;
+assert.equal(board[y * game.width + x], 2);
+
+// reversing is ignored, the snake keeps going right
+assert.equal(game.step("left"), true);
+assert.equal(game.snake()[0], (x + 1) % game.width);
+assert.equal(game.step("up"), true);
+assert.equal(game.direction, "up");
+assert.equal(game.step(), true);
+assert.equal(game.tick, 3);
+assert.throws(() => game.step("sideways"));
+assert.throws(() => new Game(2, 2, 0));
+assert.throws(() => new Game(3, 10, 0));
+
+const turns = ["down", "left", "up", "right"];
+
+// the smallest board plays until the snake runs into itself
+const small = new Game(4, 4, 0);
+for (let i = 0; small.state === "running"; i++) {
+  small.step(turns[i % 4]);
+}
+assert.equal(small.state, "lost");
+
+// the same seed and moves give the same game
+const replay = new Game(10, 8, 42);
+["left", "up", undefined].forEach((direction) => replay.step(direction));
+assert.deepEqual(replay.board(), game.board());
+
+// a snake running in circles eventually bites itself
+const circling = new Game(10, 8, 1);
+for (let i = 0; circling.state === "running"; i++) {
+  circling.step(turns[i % 4]);
+}
+assert.equal(circling.state, "lost");
+assert.equal(circling.step(), false);
+
+console.log("ok");